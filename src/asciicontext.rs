@@ -1,273 +1,705 @@
 use crate::drawables::*;
 use crate::drawingcontext::*;
+use crate::settings::Settings;
+use crate::theme::Theme;
+use std::io::{self, Write};
 use termion::color;
+use unicode_width::UnicodeWidthChar;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScreenCell {
+    pub chr: char,
+    pub bg: TermColor,
+    pub fg: TermColor,
+    pub style: TextStyle,
+}
+
+pub const EMPTY_CELL: ScreenCell = ScreenCell {
+    chr: ' ',
+    bg: TermColor::Indexed(DEFAULT_COLOR.0),
+    fg: TermColor::Indexed(DEFAULT_COLOR.1),
+    style: PLAIN_STYLE,
+};
+
+//trails dimmer than this are dropped
+const TRAIL_CUTOFF: ColorLuma = 0.05;
+//symbols have no shades, their trail is a plain streak
+const TRAIL_SYMBOL: (ColorPalette, ColorLuma) = (ColorPalette::Gray, 0.6);
+
+//pixels at least this bright glow onto their neighbours
+const BLOOM_THRESHOLD: ColorLuma = 0.45;
+const BLOOM_STRENGTH: f32 = 0.4;
+//how much of the camera flash reaches empty and drawn pixels
+const FLASH_EMPTY: f32 = 0.5;
+const FLASH_SHAPE: f32 = 0.3;
+
+//right half of a wide character, nothing is printed for it
+pub const WIDE_CONTINUATION: char = '\0';
+
+//what ends up in a cell, Shade picks a character from the luma of the colors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Glyph {
+    Blank,
+    Shade,
+    Char(char),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Paint {
+    Default,
+    Indexed(u8),
+    Shade(ColorPalette, ColorLuma),
+    //truecolor mix of two palettes, the f32 is how much of the second one
+    Blend(ColorPalette, ColorPalette, f32, ColorLuma),
+}
+
+impl Paint {
+    fn lighten(self, amount: ColorLuma) -> Paint {
+        match self {
+            Paint::Shade(palette, luma) => Paint::Shade(palette, (luma + amount).min(1.0)),
+            Paint::Blend(palette, second, mix, luma) => {
+                Paint::Blend(palette, second, mix, (luma + amount).min(1.0))
+            }
+            paint => paint,
+        }
+    }
+}
+
+//one rasterizer pixel, turned into terminal colors only when the frame is resolved
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    pub glyph: Glyph,
+    pub fg: Paint,
+    pub bg: Paint,
+    pub layer: Layer,
+}
+
+pub const EMPTY_PIXEL: Cell = Cell {
+    glyph: Glyph::Blank,
+    fg: Paint::Default,
+    bg: Paint::Default,
+    layer: Layer::Empty,
+};
+
+impl Cell {
+    pub fn from_palette(palette: ColorPalette, luma: ColorLuma, theme: &Theme) -> Cell {
+        match palette {
+            ColorPalette::Symbol(symbol) => {
+                let style = theme.symbol(symbol);
+                Cell {
+                    glyph: Glyph::Char(style.glyph),
+                    fg: Paint::Indexed(style.colors.1),
+                    bg: Paint::Indexed(style.colors.0),
+                    layer: Layer::Symbol,
+                }
+            }
+            palette => Cell {
+                glyph: Glyph::Shade,
+                fg: Paint::Shade(palette, luma),
+                bg: Paint::Shade(palette, luma),
+                layer: Layer::Shape,
+            },
+        }
+    }
+
+    fn shade(&self) -> Option<(ColorPalette, ColorLuma)> {
+        match self.bg {
+            Paint::Shade(palette, luma) | Paint::Blend(palette, _, _, luma) => {
+                Some((palette, luma))
+            }
+            _ => None,
+        }
+    }
+}
 
 pub struct AsciiContext {
-    bitmap: Vec<u8>,
+    bitmap: Vec<Cell>,
     size: (u16, u16),
+    pixels: (usize, usize),
+    color_mode: ColorMode,
+    render_mode: RenderMode,
+    theme: Theme,
+    //what an untouched cell looks like with this theme
+    empty: ScreenCell,
     triangles: Vec<Triangle>,
     points: Vec<Point>,
+    lines: Vec<Line>,
+    //entities hand in outlines instead of filled triangles
+    vector: bool,
+    dither: bool,
+    trails: Option<f32>,
+    //brightest shade seen by each pixel, fading over time
+    phosphor: Vec<(ColorPalette, ColorLuma)>,
+    bloom: bool,
     text_entries: Vec<TextEntry>,
+    last_frame: Vec<ScreenCell>,
+    redraw: bool,
+    frame_bytes: usize,
+    buffer: Vec<u8>,
 }
 
 pub fn vertex_shader(input: &Vec2, camera: &Camera) -> Vec2 {
-    let _aspect_ratio: (f32, f32) = (1.0, 1.0); //camera.size.1 / camera.size.0, 1.0);
-    let char_ratio: (f32, f32) = (17.0 / 8.0, 1.0);
-    let default_height: f32 = camera.size.1 as f32 / 63.0;
+    let scale = camera.get_scale();
+    let center = camera.get_center();
+    let (sin, cos) = (-camera.get_angle()).sin_cos();
+    let local = (input.0 - center.0, input.1 - center.1);
+    let rotated = (local.0 * cos - local.1 * sin, local.0 * sin + local.1 * cos);
     (
-        input.0 * default_height * camera.zoom * char_ratio.0 + camera.size.0 / 2.0,
-        input.1 * default_height * camera.zoom * char_ratio.1 + camera.size.1 / 2.0,
+        rotated.0 * scale.0 + camera.size.0 / 2.0,
+        rotated.1 * scale.1 + camera.size.1 / 2.0,
     )
 }
 
 impl AsciiContext {
-    pub fn new(size: (u16, u16)) -> AsciiContext {
-        let bitmap: Vec<u8> = vec![0; (size.0 * size.1) as usize];
+    pub fn new(size: (u16, u16), settings: &Settings) -> AsciiContext {
+        let sub = settings.render_mode.sub_size();
+        //a wide terminal times four braille rows overflows u16
+        let pixels = (
+            size.0 as usize * sub.0 as usize,
+            size.1 as usize * sub.1 as usize,
+        );
+        let bitmap: Vec<Cell> = vec![EMPTY_PIXEL; pixels.0 * pixels.1];
 
         AsciiContext {
             bitmap,
             size,
+            pixels,
+            color_mode: settings.color_mode,
+            render_mode: settings.render_mode,
+            theme: settings.theme.clone(),
+            empty: ScreenCell {
+                bg: TermColor::Indexed(settings.theme.default_color.0),
+                fg: TermColor::Indexed(settings.theme.default_color.1),
+                ..EMPTY_CELL
+            },
             triangles: Vec::with_capacity(100),
             points: Vec::with_capacity(100),
+            lines: Vec::with_capacity(100),
+            vector: settings.vector,
+            dither: settings.dither,
+            trails: settings.trails,
+            bloom: !settings.reduced_motion,
+            phosphor: vec![(ColorPalette::Gray, 0.0); pixels.0 * pixels.1],
             text_entries: Vec::with_capacity(10),
+            last_frame: Vec::new(),
+            redraw: true,
+            frame_bytes: 0,
+            buffer: Vec::with_capacity(size.0 as usize * size.1 as usize * 4),
         }
     }
 
-    pub fn set(&mut self, pos: (u16, u16), v: u8) {
-        let i = pos.1 * self.size.0 + pos.0;
-        if pos.0 < self.size.0 && pos.1 < self.size.1 && i < self.size.0 * self.size.1 {
-            self.bitmap[i as usize] = v;
-        }
+    pub fn frame_bytes(&self) -> usize {
+        self.frame_bytes
     }
 
-    fn get_indexes(&self, luma: u8, color_len: usize) -> (usize, usize) {
-        let char_len: u8 = CHARS_GRADIENT.len() as u8;
-        let color_len: u8 = color_len as u8;
-        let v_col = luma * color_len / PALETTE_RANGE;
-        let v_char = ((luma * color_len) % PALETTE_RANGE) * char_len / PALETTE_RANGE;
+    pub fn set(&mut self, pos: (u16, u16), cell: Cell) {
+        let (x, y) = (pos.0 as usize, pos.1 as usize);
+        if x < self.pixels.0 && y < self.pixels.1 {
+            let i = y * self.pixels.0 + x;
+            if cell.layer >= self.bitmap[i].layer {
+                self.bitmap[i] = cell;
+            }
+        }
+    }
 
-        (v_col as usize, v_char as usize)
+    //from terminal cell coordinates to rasterizer pixels
+    fn to_pixels(&self, position: Vec2) -> Vec2 {
+        let sub = self.render_mode.sub_size();
+        (position.0 * sub.0 as f32, position.1 * sub.1 as f32)
     }
 
-    fn red_palette(&self, luma: u8) -> ((&str, &str), char) {
-        let (v_col, v_char) = self.get_indexes(luma, RED_PALETTE.len());
-        (RED_PALETTE[v_col], CHARS_GRADIENT[v_char])
+    //ordered dither offset of one luma step, the step is what the current color mode can show
+    fn dither_offset(&self, x: usize, y: usize) -> f32 {
+        if !self.dither {
+            return 0.0;
+        }
+        let step = match self.color_mode {
+            ColorMode::TrueColor => return 0.0,
+            ColorMode::NoColor => 1.0 / self.theme.glyphs.len() as f32,
+            _ => 1.0 / PALETTE_RANGE as f32,
+        };
+        (bayer(x, y) - 0.5) * step
     }
 
-    fn green_palette(&self, luma: u8) -> ((&str, &str), char) {
-        let (v_col, v_char) = self.get_indexes(luma, GREEN_PALETTE.len());
-        (GREEN_PALETTE[v_col], CHARS_GRADIENT[v_char])
+    //lights an empty pixel with a palette or adds to the luma of a shaded one
+    fn brighten(&mut self, i: usize, palette: ColorPalette, empty: ColorLuma, shape: ColorLuma) {
+        let pixel = self.bitmap[i];
+        match (pixel.layer, pixel.shade()) {
+            (Layer::Empty, _) if empty >= TRAIL_CUTOFF => {
+                self.bitmap[i] = Cell {
+                    layer: Layer::Background,
+                    ..Cell::from_palette(palette, empty, &self.theme)
+                }
+            }
+            (Layer::Background, Some((_, luma))) if empty > luma => {
+                self.bitmap[i] = Cell {
+                    layer: Layer::Background,
+                    ..Cell::from_palette(palette, empty, &self.theme)
+                }
+            }
+            (Layer::Shape, Some(_)) => {
+                self.bitmap[i] = Cell {
+                    fg: pixel.fg.lighten(shape),
+                    bg: pixel.bg.lighten(shape),
+                    ..pixel
+                }
+            }
+            _ => {}
+        }
     }
 
-    fn blue_palette(&self, luma: u8) -> ((&str, &str), char) {
-        let (v_col, v_char) = self.get_indexes(luma, BLUE_PALETTE.len());
-        (BLUE_PALETTE[v_col], CHARS_GRADIENT[v_char])
+    //pixel shaded from several weighted palettes, truecolor mixes the two strongest ones
+    //and the other modes dither between all of them
+    fn blend_cell(
+        &self,
+        shades: &[(ColorPalette, f32)],
+        luma: ColorLuma,
+        pos: (usize, usize),
+    ) -> Cell {
+        //most shapes use one palette, nothing to blend then
+        if shades.iter().all(|(palette, _)| *palette == shades[0].0) {
+            return Cell::from_palette(shades[0].0, luma, &self.theme);
+        }
+
+        //triangles pass three corners and lines two, this runs per pixel so it stays on the stack
+        let mut merged = [(shades[0].0, 0.0); 3];
+        let mut len = 0;
+        for &(palette, weight) in shades {
+            match merged[..len].iter_mut().find(|(p, _)| *p == palette) {
+                Some((_, w)) => *w += weight.max(0.0),
+                None => {
+                    merged[len] = (palette, weight.max(0.0));
+                    len += 1;
+                }
+            }
+        }
+        let weights = &mut merged[..len];
+
+        let symbol = weights
+            .iter()
+            .any(|(palette, _)| matches!(palette, ColorPalette::Symbol(_)));
+        if symbol {
+            return Cell::from_palette(shades[0].0, luma, &self.theme);
+        }
+
+        if self.color_mode == ColorMode::TrueColor {
+            weights.sort_by(|a, b| b.1.total_cmp(&a.1));
+            let (first, second) = (weights[0], weights[1]);
+            let mix = second.1 / (first.1 + second.1).max(f32::EPSILON);
+            let paint = Paint::Blend(first.0, second.0, mix, luma);
+            return Cell {
+                fg: paint,
+                bg: paint,
+                ..Cell::from_palette(first.0, luma, &self.theme)
+            };
+        }
+
+        let total: f32 = weights.iter().map(|(_, w)| w).sum();
+        let threshold = bayer(pos.0, pos.1) * total;
+        let mut sum = 0.0;
+        for &(palette, weight) in weights.iter() {
+            sum += weight;
+            if sum > threshold {
+                return Cell::from_palette(palette, luma, &self.theme);
+            }
+        }
+        Cell::from_palette(weights[weights.len() - 1].0, luma, &self.theme)
     }
 
-    fn yellow_palette(&self, luma: u8) -> ((&str, &str), char) {
-        let (v_col, v_char) = self.get_indexes(luma, YELLOW_PALETTE.len());
-        (YELLOW_PALETTE[v_col], CHARS_GRADIENT[v_char])
+    fn get_indexes(&self, luma: u8, color_len: usize) -> (usize, usize) {
+        let char_len = self.theme.glyphs.len();
+        let luma = luma as usize;
+        let range = PALETTE_RANGE as usize;
+        let v_col = luma * color_len / range;
+        let v_char = ((luma * color_len) % range) * char_len / range;
+
+        (v_col, v_char)
     }
 
-    fn magenta_palette(&self, luma: u8) -> ((&str, &str), char) {
-        let (v_col, v_char) = self.get_indexes(luma, MAGENTA_PALETTE.len());
-        (MAGENTA_PALETTE[v_col], CHARS_GRADIENT[v_char])
+    //256-color (bg, fg) pair and gradient character for a shade
+    fn palette_color(&self, palette: ColorPalette, luma: ColorLuma) -> ((u8, u8), usize) {
+        match self.theme.palette(palette) {
+            Some(table) => {
+                let level = ((luma * PALETTE_RANGE as f32) as u8).min(PALETTE_RANGE - 1);
+                let (v_col, v_char) = self.get_indexes(level, table.pairs.len());
+                (table.pairs[v_col], v_char)
+            }
+            None => (self.theme.default_color, 0),
+        }
     }
 
-    fn cyan_palette(&self, luma: u8) -> ((&str, &str), char) {
-        let (v_col, v_char) = self.get_indexes(luma, CYAN_PALETTE.len());
-        (CYAN_PALETTE[v_col], CHARS_GRADIENT[v_char])
+    fn shade_rgb(&self, palette: ColorPalette, luma: ColorLuma) -> TermColor {
+        match self.theme.palette(palette) {
+            Some(table) => TermColor::Rgb(gradient_rgb(&table.gradient, luma)),
+            None => self.empty.bg,
+        }
     }
 
-    fn gray_palette(&self, luma: u8) -> ((&str, &str), char) {
-        let (v_col, v_char) = self.get_indexes(luma, GRAY_PALETTE.len());
-        (GRAY_PALETTE[v_col], CHARS_GRADIENT[v_char])
+    fn blend_rgb(
+        &self,
+        palettes: (ColorPalette, ColorPalette),
+        mix: f32,
+        luma: ColorLuma,
+    ) -> TermColor {
+        match (
+            self.shade_rgb(palettes.0, luma),
+            self.shade_rgb(palettes.1, luma),
+        ) {
+            (TermColor::Rgb(a), TermColor::Rgb(b)) => {
+                let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * mix).round() as u8;
+                TermColor::Rgb((lerp(a.0, b.0), lerp(a.1, b.1), lerp(a.2, b.2)))
+            }
+            (color, _) => color,
+        }
     }
 
-    fn fill_color(&self, color: u8, last_color: u8, last_char: char) -> char {
-        if last_color == color {
-            print!("{}", last_char);
-            return last_char;
+    fn paint_color(&self, paint: Paint, background: bool) -> TermColor {
+        match paint {
+            Paint::Default => match background {
+                true => self.empty.bg,
+                false => self.empty.fg,
+            },
+            Paint::Indexed(v) => TermColor::Indexed(v),
+            Paint::Blend(palette, second, mix, luma) => match self.color_mode {
+                ColorMode::TrueColor => self.blend_rgb((palette, second), mix, luma),
+                _ => self.paint_color(Paint::Shade(palette, luma), background),
+            },
+            Paint::Shade(palette, luma) => match self.color_mode {
+                ColorMode::TrueColor => self.shade_rgb(palette, luma),
+                _ => {
+                    let ((bg, fg), _) = self.palette_color(palette, luma);
+                    match background {
+                        true => TermColor::Indexed(bg),
+                        false => TermColor::Indexed(fg),
+                    }
+                }
+            },
         }
+    }
 
-        let ((fg, bg), chr) = match color {
-            0..=15 => self.red_palette(color),
-            16..=31 => self.green_palette(color - 16),
-            32..=47 => self.blue_palette(color - 32),
-            48..=63 => self.yellow_palette(color - 48),
-            64..=79 => self.magenta_palette(color - 64),
-            80..=95 => self.cyan_palette(color - 80),
-            96..=111 => self.gray_palette(color - 96),
-            _ => (DEFAULT_COLOR, ' '),
+    fn resolve_pixel(&self, i: usize) -> ScreenCell {
+        let pixel = &self.bitmap[i];
+        let chr = match (pixel.glyph, pixel.shade()) {
+            (Glyph::Char(chr), _) => chr,
+            //truecolor shades are smooth enough with the background alone
+            (Glyph::Shade, Some((palette, luma))) => match self.color_mode {
+                ColorMode::TrueColor => {
+                    return ScreenCell {
+                        chr: ' ',
+                        bg: self.paint_color(pixel.bg, true),
+                        fg: self.empty.fg,
+                        style: PLAIN_STYLE,
+                    }
+                }
+                //without colors the whole glyph ramp goes from dark to bright
+                ColorMode::NoColor => self.ramp_glyph(luma),
+                _ => self.theme.glyphs[self.palette_color(palette, luma).1],
+            },
+            _ => ' ',
         };
 
-        print!("{}{}{}", bg, fg, chr);
+        ScreenCell {
+            chr,
+            bg: self.paint_color(pixel.bg, true),
+            fg: self.paint_color(pixel.fg, false),
+            style: PLAIN_STYLE,
+        }
+    }
 
-        return chr;
+    fn ramp_glyph(&self, luma: ColorLuma) -> char {
+        let len = self.theme.glyphs.len();
+        self.theme.glyphs[((luma * len as f32) as usize).min(len - 1)]
     }
 
-    pub fn draw_triangles(&mut self, camera: &Camera) {
-        let shaded_triangles: Vec<Triangle> = self
-            .triangles
-            .iter()
-            .map(|tri| {
-                //vertex shader
-                Triangle {
-                    points: [
-                        vertex_shader(&tri.points[0], camera),
-                        vertex_shader(&tri.points[1], camera),
-                        vertex_shader(&tri.points[2], camera),
-                    ],
-                    colors: tri.colors,
-                    color_palette: tri.color_palette.clone(),
+    //a single color for a pixel, used when a cell is split between several pixels
+    fn pixel_color(&self, i: usize) -> TermColor {
+        match (self.color_mode, self.bitmap[i].shade()) {
+            (ColorMode::TrueColor, _) => self.paint_color(self.bitmap[i].bg, true),
+            (_, Some((palette, luma))) => {
+                let ((bg, fg), v_char) = self.palette_color(palette, luma);
+                match v_char * 2 >= self.theme.glyphs.len() {
+                    true => TermColor::Indexed(fg),
+                    false => TermColor::Indexed(bg),
                 }
-            })
-            .collect();
+            }
+            _ => self.paint_color(self.bitmap[i].bg, true),
+        }
+    }
 
-        shaded_triangles.iter().for_each(|tri| {
-            //find the extremities of the triangle
-            let top = tri.points[0].1.min(tri.points[1].1).min(tri.points[2].1);
-            let bot = tri.points[0].1.max(tri.points[1].1).max(tri.points[2].1);
+    fn resolve_cell(&self, x: usize, y: usize) -> ScreenCell {
+        let width = self.pixels.0;
+        match self.render_mode {
+            RenderMode::Cell => self.resolve_pixel(y * width + x),
+            RenderMode::HalfBlock => {
+                let top = y * 2 * width + x;
+                let bottom = top + width;
 
-            let vector_len = (bot - top) as usize + 1;
-            //create the vector of lines
-            let mut line_segments: Vec<(u16, u16, u16)> =
-                vec![(u16::MAX, u16::MAX, u16::MAX); vector_len];
+                //symbols keep their glyph
+                if self.bitmap[top].layer >= Layer::Symbol {
+                    return self.resolve_pixel(top);
+                }
+                if self.bitmap[bottom].layer >= Layer::Symbol {
+                    return self.resolve_pixel(bottom);
+                }
 
-            //all edges
-            [
-                (tri.points[0], tri.points[1]),
-                (tri.points[1], tri.points[2]),
-                (tri.points[2], tri.points[0]),
-            ]
-            .iter()
-            .map(|(a, b)| ((a.0 as u16, a.1 as u16), (b.0 as u16, b.1 as u16)))
-            .map(|(a, b)| {
-                //first point is the top one
-                if b.1 < a.1 {
-                    return (b, a);
+                //only the shape of the pixels can be shown without colors
+                if self.color_mode == ColorMode::NoColor {
+                    let chr = match (
+                        self.bitmap[top].layer > Layer::Empty,
+                        self.bitmap[bottom].layer > Layer::Empty,
+                    ) {
+                        (true, true) => CHAR_FULL,
+                        (true, false) => CHAR_HALF_TOP,
+                        (false, true) => CHAR_HALF_BOTTOM,
+                        (false, false) => ' ',
+                    };
+                    return ScreenCell { chr, ..self.empty };
                 }
-                return (a, b);
-            })
-            .for_each(|(a, b)| {
-                let a_y = a.1 as usize;
-                let b_y = b.1 as usize;
-                let t = top as usize;
-                if a_y != b_y {
-                    for y in a_y..b_y {
-                        let segment = line_segments[y - t];
-                        let computed_x = (a.0 as f32
-                            + ((y as f32) - a.1 as f32) * (b.0 as f32 - a.0 as f32)
-                                / (b.1 as f32 - a.1 as f32))
-                            as u16;
-                        if segment.0 == u16::MAX {
-                            line_segments[y - t] = (computed_x, u16::MAX, y as u16);
-                        } else {
-                            line_segments[y - t] = (
-                                computed_x.min(segment.0),
-                                computed_x.max(segment.0),
-                                y as u16,
-                            );
+
+                let top_color = self.pixel_color(top);
+                let bottom_color = self.pixel_color(bottom);
+                if top_color == bottom_color {
+                    let full = self.resolve_pixel(top);
+                    if full == self.resolve_pixel(bottom) {
+                        return full;
+                    }
+                    return ScreenCell {
+                        chr: ' ',
+                        bg: bottom_color,
+                        fg: self.empty.fg,
+                        style: PLAIN_STYLE,
+                    };
+                }
+
+                ScreenCell {
+                    chr: CHAR_HALF_TOP,
+                    bg: bottom_color,
+                    fg: top_color,
+                    style: PLAIN_STYLE,
+                }
+            }
+            RenderMode::Braille => {
+                let mut dots: u32 = 0;
+                //one entry per dot at most
+                let mut palettes = [(ColorPalette::Gray, 0usize); 8];
+                let mut len = 0;
+                let mut brightest: Option<(ColorPalette, ColorLuma)> = None;
+
+                for (dy, row) in BRAILLE_DOTS.iter().enumerate() {
+                    for (dx, bit) in row.iter().enumerate() {
+                        let i = (y * 4 + dy) * width + x * 2 + dx;
+                        let pixel = &self.bitmap[i];
+                        match pixel.layer {
+                            Layer::Empty => {}
+                            //symbols keep their glyph
+                            Layer::Symbol | Layer::Text => return self.resolve_pixel(i),
+                            Layer::Background | Layer::Shape => {
+                                dots |= bit;
+                                if let Some((palette, luma)) = pixel.shade() {
+                                    match palettes[..len].iter_mut().find(|(p, _)| *p == palette) {
+                                        Some((_, count)) => *count += 1,
+                                        None => {
+                                            palettes[len] = (palette, 1);
+                                            len += 1;
+                                        }
+                                    }
+                                    if brightest.is_none_or(|(_, b)| b < luma) {
+                                        brightest = Some((palette, luma));
+                                    }
+                                }
+                            }
                         }
                     }
                 }
-            });
 
-            line_segments.iter().for_each(|(x0, x1, y)| {
-                for x in *x0..=*x1 {
-                    let color = ((get_barycentric((x as f32, *y as f32), &tri)
-                        * PALETTE_RANGE as f32) as u8)
-                        .min(PALETTE_RANGE - 1);
-                    let color_offset = match tri.color_palette {
-                        ColorPalette::Red => 1,
-                        ColorPalette::Green => 17,
-                        ColorPalette::Blue => 33,
-                        ColorPalette::Yellow => 49,
-                        ColorPalette::Magenta => 65,
-                        ColorPalette::Cyan => 81,
-                        ColorPalette::Gray => 97,
-                        ColorPalette::Custom => 0,
-                    };
-                    self.set((x, *y), color + color_offset);
+                match brightest {
+                    None => self.empty,
+                    Some((palette, luma)) => {
+                        //the most used palette colors the whole cell
+                        let dominant = palettes[..len]
+                            .iter()
+                            .rev()
+                            .max_by_key(|(_, count)| *count)
+                            .map_or(palette, |(p, _)| *p);
+
+                        ScreenCell {
+                            chr: char::from_u32(CHAR_BRAILLE + dots).unwrap_or(' '),
+                            bg: self.empty.bg,
+                            fg: self.dot_color(dominant, luma),
+                            style: PLAIN_STYLE,
+                        }
+                    }
                 }
-            });
-        });
+            }
+        }
     }
 
-    pub fn draw_points(&mut self, camera: &Camera) {
-        let shaded_points: Vec<Point> = self
-            .points
-            .iter()
-            .map(|p| Point {
-                position: vertex_shader(&p.position, camera),
-                color: p.color,
-                color_palette: p.color_palette.clone(),
-            })
-            .collect();
-        shaded_points.iter().for_each(|p| {
-            let color = match p.color_palette {
-                ColorPalette::Custom => p.color as u8,
-                _ => (p.color * PALETTE_RANGE as f32) as u8,
-            };
-            let color_offset = match p.color_palette {
-                ColorPalette::Red => 1,
-                ColorPalette::Green => 17,
-                ColorPalette::Blue => 33,
-                ColorPalette::Yellow => 49,
-                ColorPalette::Magenta => 65,
-                ColorPalette::Cyan => 81,
-                ColorPalette::Gray => 97,
-                ColorPalette::Custom => 0,
-            };
-            self.set(
-                (p.position.0 as u16, p.position.1 as u16),
-                color + color_offset,
-            );
-        });
+    //dots are drawn on black, so they always take the bright end of the palette
+    fn dot_color(&self, palette: ColorPalette, luma: ColorLuma) -> TermColor {
+        match self.color_mode {
+            ColorMode::TrueColor => self.shade_rgb(palette, 0.4 + luma * 0.6),
+            _ => {
+                let ((_, fg), _) = self.palette_color(palette, luma);
+                TermColor::Indexed(fg)
+            }
+        }
     }
 
-    pub fn display_text(&self) {
-        self.text_entries.iter().for_each(|text| {
-            let posx = text.position.0 as u16 + 1;
-            let posy = text.position.1 as u16 + 1;
-            print!("{}", termion::cursor::Goto(posx, posy));
-            match text.color_palette {
-                TextColorPalette::Text => {
-                    print!("{}{}", color::Bg(color::Black), color::Fg(color::White))
+    //highest layer drawn inside a terminal cell
+    fn cell_layer(&self, x: usize, y: usize) -> Layer {
+        let sub = self.render_mode.sub_size();
+        let width = self.pixels.0;
+        let mut layer = Layer::Empty;
+        for dy in 0..sub.1 as usize {
+            for dx in 0..sub.0 as usize {
+                let pixel =
+                    &self.bitmap[(y * sub.1 as usize + dy) * width + x * sub.0 as usize + dx];
+                if pixel.layer > layer {
+                    layer = pixel.layer;
                 }
-                TextColorPalette::Menu => {
-                    print!("{}{}", color::Bg(color::Blue), color::Fg(color::LightCyan))
+            }
+        }
+        layer
+    }
+
+    //the glyphs and colors that end up on screen, text included
+    pub fn resolve_frame(&self) -> Vec<ScreenCell> {
+        let width = self.size.0 as usize;
+        let height = self.size.1 as usize;
+        let mut frame: Vec<ScreenCell> = (0..width * height)
+            .map(|i| self.resolve_cell(i % width, i / width))
+            .collect();
+
+        //later entries go over earlier ones, geometry on a higher layer stays on top
+        self.text_entries.iter().for_each(|text| {
+            let (bg, fg) = self.theme.text_colors(text.color_palette);
+            let style = text.style;
+            clip_text(text, self.size).for_each(|(x, y, chr, chr_width)| {
+                if (x..x + chr_width).any(|x| text.layer < self.cell_layer(x, y)) {
+                    return;
                 }
-                TextColorPalette::Warning => {
-                    print!("{}{}", color::Bg(color::LightRed), color::Fg(color::Black))
+                let row = &mut frame[y * width..(y + 1) * width];
+                put_text_cell(row, x, ScreenCell { chr, bg, fg, style });
+                if chr_width == 2 {
+                    let chr = WIDE_CONTINUATION;
+                    put_text_cell(row, x + 1, ScreenCell { chr, bg, fg, style });
                 }
-            };
-            print!("{}", text.string);
+            });
         });
+
+        frame
+    }
+
+    //only the text, for screens that draw their own background
+    pub fn display_text(&mut self, out: &mut dyn Write) -> io::Result<()> {
+        self.buffer.clear();
+        for text in self.text_entries.iter() {
+            let (bg, fg) = self.theme.text_colors(text.color_palette);
+            let mut cursor: Option<(usize, usize)> = None;
+            for (x, y, chr, chr_width) in clip_text(text, self.size) {
+                if cursor != Some((x, y)) {
+                    let goto = termion::cursor::Goto(x as u16 + 1, y as u16 + 1);
+                    write!(self.buffer, "{}", goto)?;
+                    write_style(&mut self.buffer, text.style)?;
+                    write_bg(&mut self.buffer, bg, self.color_mode)?;
+                    write_fg(&mut self.buffer, fg, self.color_mode)?;
+                }
+                write!(self.buffer, "{}", chr)?;
+                cursor = Some((x + chr_width, y));
+            }
+        }
+        write_style(&mut self.buffer, PLAIN_STYLE)?;
+
+        out.write_all(&self.buffer)?;
+        out.flush()
     }
 }
 
+//the characters of a text entry that fit inside the screen, with their cell and width
+//zero width characters are dropped, wide ones are dropped when cut by the edge
+fn clip_text(
+    text: &TextEntry,
+    size: (u16, u16),
+) -> impl Iterator<Item = (usize, usize, char, usize)> + '_ {
+    let mut x = text.left().floor() as i32;
+    let y = text.position.1.floor() as i32;
+    let visible_row = y >= 0 && y < size.1 as i32;
+    text.string.chars().filter_map(move |chr| {
+        let chr_width = chr.width().unwrap_or(0) as i32;
+        let start = x;
+        x += chr_width;
+        match visible_row && chr_width > 0 && start >= 0 && x <= size.0 as i32 {
+            true => Some((start as usize, y as usize, chr, chr_width as usize)),
+            false => None,
+        }
+    })
+}
+
+//writes a text cell into a row, without leaving half of a wide character behind
+//the wide character being overwritten is cleared at its own head and continuation
+fn put_text_cell(row: &mut [ScreenCell], x: usize, cell: ScreenCell) {
+    if row[x].chr == WIDE_CONTINUATION && x > 0 {
+        row[x - 1].chr = ' ';
+    }
+    if x + 1 < row.len() && row[x + 1].chr == WIDE_CONTINUATION {
+        row[x + 1].chr = ' ';
+    }
+    row[x] = cell;
+}
+
+//the world wraps around at the camera bounds, so anything sticking out of one
+//edge is drawn again on the opposite edge, as long as the camera can see that copy
+fn wrap_offsets(min: Vec2, max: Vec2, bounds: Vec2, view: (Vec2, Vec2)) -> Vec<Vec2> {
+    let period = (bounds.0 * 2.0, bounds.1 * 2.0);
+    let (view_min, view_max) = view;
+    let offsets_x: Vec<f32> = [0.0, -period.0, period.0]
+        .into_iter()
+        .filter(|&x| x == 0.0 || (min.0 + x < view_max.0 && max.0 + x > view_min.0))
+        .collect();
+    let offsets_y: Vec<f32> = [0.0, -period.1, period.1]
+        .into_iter()
+        .filter(|&y| y == 0.0 || (min.1 + y < view_max.1 && max.1 + y > view_min.1))
+        .collect();
+
+    offsets_x
+        .iter()
+        .flat_map(|&x| offsets_y.iter().map(move |&y| (x, y)))
+        .collect()
+}
+
 fn edge_function(a: Vec2, b: Vec2, c: Vec2) -> f32 {
     (c.0 - a.0) * (b.1 - a.1) - (c.1 - a.1) * (b.0 - a.0)
 }
 
-fn get_barycentric(point: Vec2, triangle: &Triangle) -> ColorLuma {
+//weight of each vertex at a point
+fn get_barycentric(point: Vec2, triangle: &Triangle) -> [f32; 3] {
     let area: f32 = edge_function(triangle.points[0], triangle.points[1], triangle.points[2]);
     let w0: f32 = edge_function(triangle.points[1], triangle.points[2], point) / area;
     let w1: f32 = edge_function(triangle.points[2], triangle.points[0], point) / area;
     let w2: f32 = edge_function(triangle.points[0], triangle.points[1], point) / area;
 
-    triangle.colors[0] * w0 + triangle.colors[1] * w1 + triangle.colors[2] * w2
+    [w0, w1, w2]
 }
 
 impl DrawingContext for AsciiContext {
     fn resize(&mut self, size: (u16, u16)) {
-        self.bitmap = vec![0; (size.0 * size.1) as usize];
+        let sub = self.render_mode.sub_size();
+        self.size = size;
+        self.pixels = (
+            size.0 as usize * sub.0 as usize,
+            size.1 as usize * sub.1 as usize,
+        );
+        self.bitmap = vec![EMPTY_PIXEL; self.pixels.0 * self.pixels.1];
+        self.phosphor = vec![(ColorPalette::Gray, 0.0); self.bitmap.len()];
+        //the terminal may have reflowed the old frame, nothing on screen can be trusted
+        self.last_frame.clear();
+        self.redraw = true;
     }
 
     fn clear(&mut self) {
+        let len = self.pixels.0 * self.pixels.1;
         self.bitmap.clear();
-        self.bitmap.resize((self.size.0 * self.size.1) as usize, 0);
+        self.bitmap.resize(len, EMPTY_PIXEL);
     }
 
     fn flush_text_entries(&mut self) {
@@ -282,6 +714,10 @@ impl DrawingContext for AsciiContext {
         self.points.resize(0, EMPTY_POINT);
     }
 
+    fn flush_lines(&mut self) {
+        self.lines.resize(0, EMPTY_LINE);
+    }
+
     fn add_text_entry(&mut self, text_entry: &TextEntry) {
         self.text_entries.push(text_entry.clone());
     }
@@ -300,81 +736,455 @@ impl DrawingContext for AsciiContext {
         points.iter().for_each(|p| self.points.push(p.clone()));
     }
 
-    fn display(&self) {
-        print!("{}", termion::cursor::Goto(1, 1));
-        for (i, line) in self.bitmap.chunks(self.size.0 as usize).enumerate() {
-            if i != 0 {
-                print!("\n");
-
-                self.text_entries.iter().for_each(|text| {
-                    if i - 1 == text.position.1 as usize {
-                        let posx = text.position.0 as u16 + 1;
-                        let posy = text.position.1 as u16 + 1;
-                        print!("{}", termion::cursor::Goto(posx, posy));
-                        match text.color_palette {
-                            TextColorPalette::Text => {
-                                print!("{}{}", color::Bg(color::Black), color::Fg(color::White))
-                            }
-                            TextColorPalette::Menu => {
-                                print!("{}{}", color::Bg(color::Blue), color::Fg(color::LightCyan))
-                            }
-                            TextColorPalette::Warning => {
-                                print!("{}{}", color::Bg(color::LightRed), color::Fg(color::Black))
-                            }
-                        };
-                        print!("{}", text.string);
+    fn add_lines(&mut self, lines: &[Line]) {
+        lines.iter().for_each(|l| self.lines.push(l.clone()));
+    }
+
+    fn draw_triangles(&mut self, camera: &Camera) {
+        let bounds = camera.get_bounds();
+        let view = camera.get_view();
+        let shaded_triangles: Vec<Triangle> = self
+            .triangles
+            .iter()
+            .flat_map(|tri| {
+                let min = (
+                    tri.points[0].0.min(tri.points[1].0).min(tri.points[2].0),
+                    tri.points[0].1.min(tri.points[1].1).min(tri.points[2].1),
+                );
+                let max = (
+                    tri.points[0].0.max(tri.points[1].0).max(tri.points[2].0),
+                    tri.points[0].1.max(tri.points[1].1).max(tri.points[2].1),
+                );
+                wrap_offsets(min, max, bounds, view)
+                    .into_iter()
+                    .map(move |offset| (tri, offset))
+            })
+            .map(|(tri, offset)| {
+                //vertex shader
+                let shift = |p: Vec2| (p.0 + offset.0, p.1 + offset.1);
+                Triangle {
+                    points: [
+                        self.to_pixels(vertex_shader(&shift(tri.points[0]), camera)),
+                        self.to_pixels(vertex_shader(&shift(tri.points[1]), camera)),
+                        self.to_pixels(vertex_shader(&shift(tri.points[2]), camera)),
+                    ],
+                    colors: tri.colors,
+                    color_palettes: tri.color_palettes,
+                }
+            })
+            .collect();
+
+        let width = self.pixels.0 as i32;
+        let height = self.pixels.1 as i32;
+
+        shaded_triangles.iter().for_each(|tri| {
+            //find the extremities of the triangle, clipped to the viewport
+            let top = tri.points[0].1.min(tri.points[1].1).min(tri.points[2].1);
+            let bot = tri.points[0].1.max(tri.points[1].1).max(tri.points[2].1);
+            let y_start = (top.floor() as i32).max(0);
+            let y_end = (bot.floor() as i32).min(height - 1);
+            if y_start > y_end {
+                return;
+            }
+
+            //create the vector of lines, one (left, right) span for each row
+            let mut line_segments: Vec<(i32, i32)> =
+                vec![(i32::MAX, i32::MIN); (y_end - y_start + 1) as usize];
+
+            //all edges
+            [
+                (tri.points[0], tri.points[1]),
+                (tri.points[1], tri.points[2]),
+                (tri.points[2], tri.points[0]),
+            ]
+            .iter()
+            .map(|(a, b)| ((a.0.floor(), a.1.floor()), (b.0.floor(), b.1.floor())))
+            .map(|(a, b)| {
+                //first point is the top one
+                if b.1 < a.1 {
+                    return (b, a);
+                }
+                (a, b)
+            })
+            .for_each(|(a, b)| {
+                let a_y = (a.1 as i32).max(y_start);
+                let b_y = (b.1 as i32).min(y_end + 1);
+                for y in a_y..b_y {
+                    let computed_x =
+                        (a.0 + (y as f32 - a.1) * (b.0 - a.0) / (b.1 - a.1)).floor() as i32;
+                    let segment = &mut line_segments[(y - y_start) as usize];
+                    segment.0 = segment.0.min(computed_x);
+                    segment.1 = segment.1.max(computed_x);
+                }
+            });
+
+            line_segments
+                .iter()
+                .enumerate()
+                .filter(|(_, (x0, x1))| x0 <= x1)
+                .for_each(|(row, (x0, x1))| {
+                    let y = y_start + row as i32;
+                    for x in (*x0).max(0)..=(*x1).min(width - 1) {
+                        let weights = get_barycentric((x as f32, y as f32), tri);
+                        let luma = tri.colors[0] * weights[0]
+                            + tri.colors[1] * weights[1]
+                            + tri.colors[2] * weights[2]
+                            + self.dither_offset(x as usize, y as usize);
+                        let shades = [
+                            (tri.color_palettes[0], weights[0]),
+                            (tri.color_palettes[1], weights[1]),
+                            (tri.color_palettes[2], weights[2]),
+                        ];
+                        let cell = self.blend_cell(
+                            &shades,
+                            luma.clamp(0.0, 1.0),
+                            (x as usize, y as usize),
+                        );
+                        self.set((x as u16, y as u16), cell);
                     }
                 });
+        });
+    }
+
+    fn draw_points(&mut self, camera: &Camera) {
+        let bounds = camera.get_bounds();
+        let view = camera.get_view();
+        let shaded_points: Vec<Point> = self
+            .points
+            .iter()
+            .flat_map(|p| {
+                wrap_offsets(p.position, p.position, bounds, view)
+                    .into_iter()
+                    .map(move |offset| (p, offset))
+            })
+            .map(|(p, offset)| Point {
+                position: self.to_pixels(vertex_shader(
+                    &(p.position.0 + offset.0, p.position.1 + offset.1),
+                    camera,
+                )),
+                color: p.color,
+                color_palette: p.color_palette,
+            })
+            .collect();
+        shaded_points.iter().for_each(|p| {
+            if p.position.0 < 0.0 || p.position.1 < 0.0 {
+                return;
+            }
+            let cell = Cell::from_palette(p.color_palette, p.color.clamp(0.0, 1.0), &self.theme);
+            self.set((p.position.0 as u16, p.position.1 as u16), cell);
+        });
+    }
+
+    fn draw_lines(&mut self, camera: &Camera) {
+        let bounds = camera.get_bounds();
+        let view = camera.get_view();
+        let shaded_lines: Vec<Line> = self
+            .lines
+            .iter()
+            .flat_map(|l| {
+                let min = (
+                    l.points[0].0.min(l.points[1].0),
+                    l.points[0].1.min(l.points[1].1),
+                );
+                let max = (
+                    l.points[0].0.max(l.points[1].0),
+                    l.points[0].1.max(l.points[1].1),
+                );
+                wrap_offsets(min, max, bounds, view)
+                    .into_iter()
+                    .map(move |offset| (l, offset))
+            })
+            .map(|(l, offset)| {
+                let shift = |p: Vec2| (p.0 + offset.0, p.1 + offset.1);
+                Line {
+                    points: [
+                        self.to_pixels(vertex_shader(&shift(l.points[0]), camera)),
+                        self.to_pixels(vertex_shader(&shift(l.points[1]), camera)),
+                    ],
+                    colors: l.colors,
+                    color_palettes: l.color_palettes,
+                }
+            })
+            .collect();
 
-                print!("{}", termion::cursor::Goto(1, (i + 1) as u16));
-                print!("{}{}", color::Bg(color::Black), color::Fg(color::White));
+        shaded_lines.iter().for_each(|l| {
+            //bresenham, stepping one pixel at a time along the major axis
+            let (x0, y0) = (l.points[0].0.floor() as i32, l.points[0].1.floor() as i32);
+            let (x1, y1) = (l.points[1].0.floor() as i32, l.points[1].1.floor() as i32);
+            let dx = (x1 - x0).abs();
+            let dy = -(y1 - y0).abs();
+            let sx = if x0 < x1 { 1 } else { -1 };
+            let sy = if y0 < y1 { 1 } else { -1 };
+            let steps = dx.max(-dy).max(1) as f32;
+
+            let (mut x, mut y) = (x0, y0);
+            let mut err = dx + dy;
+            let mut step = 0.0;
+            loop {
+                if x >= 0 && y >= 0 {
+                    let t = step / steps;
+                    let luma = (l.colors[0] + (l.colors[1] - l.colors[0]) * t).clamp(0.0, 1.0);
+                    let shades = [(l.color_palettes[0], 1.0 - t), (l.color_palettes[1], t)];
+                    let cell = self.blend_cell(&shades, luma, (x as usize, y as usize));
+                    self.set((x as u16, y as u16), cell);
+                }
+                if x == x1 && y == y1 {
+                    break;
+                }
+                let e2 = 2 * err;
+                if e2 >= dy {
+                    err += dy;
+                    x += sx;
+                }
+                if e2 <= dx {
+                    err += dx;
+                    y += sy;
+                }
+                step += 1.0;
             }
+        });
+    }
 
-            let mut was_colored = false;
-            let mut last_pixel: u8 = 0;
-            let mut last_char: char = '!';
-
-            for &pixel in line.iter() {
-                match pixel {
-                    0 => {
-                        if was_colored {
-                            print!("{}{}", color::Bg(color::Black), color::Fg(color::White));
-                            was_colored = false;
-                            last_pixel = 0;
+    fn vector(&self) -> bool {
+        self.vector
+    }
+
+    //post-process before display, the previous frames fade behind the current one
+    fn persist(&mut self, delta: f32) {
+        let half_life = match self.trails {
+            Some(half_life) => half_life,
+            None => return,
+        };
+        let decay = 0.5f32.powf(delta / half_life);
+
+        for i in 0..self.bitmap.len() {
+            let (palette, luma) = self.phosphor[i];
+            let luma = luma * decay;
+            let pixel = self.bitmap[i];
+
+            self.phosphor[i] = match (pixel.layer, pixel.shade()) {
+                (Layer::Empty, _) if luma < TRAIL_CUTOFF => (palette, 0.0),
+                (Layer::Empty, _) => {
+                    self.bitmap[i] = Cell {
+                        layer: Layer::Background,
+                        ..Cell::from_palette(palette, luma, &self.theme)
+                    };
+                    (palette, luma)
+                }
+                (_, Some((shade, shade_luma))) if shade_luma >= luma => (shade, shade_luma),
+                (_, Some(_)) => (palette, luma),
+                (_, None) if TRAIL_SYMBOL.1 >= luma => TRAIL_SYMBOL,
+                (_, None) => (palette, luma),
+            };
+        }
+    }
+
+    //bloom around bright pixels, then the camera flash over everything but symbols
+    fn effects(&mut self, camera: &Camera) {
+        let (width, height) = self.pixels;
+
+        if self.bloom {
+            let source: Vec<Option<(ColorPalette, ColorLuma)>> = self
+                .bitmap
+                .iter()
+                .map(|pixel| pixel.shade().filter(|(_, luma)| *luma >= BLOOM_THRESHOLD))
+                .collect();
+
+            for y in 0..height {
+                for x in 0..width {
+                    let mut glow: Option<(ColorPalette, ColorLuma)> = None;
+                    for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                        for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                            if (nx, ny) == (x, y) {
+                                continue;
+                            }
+                            if let Some((palette, luma)) = source[ny * width + nx] {
+                                let luma = luma * BLOOM_STRENGTH;
+                                if glow.is_none_or(|(_, g)| g < luma) {
+                                    glow = Some((palette, luma));
+                                }
+                            }
                         }
-                        print!(" ");
-                    }
-                    128 => {
-                        print!("{}", color::Fg(color::LightWhite));
-                        print!("{}", CHAR_BALL);
-                        last_pixel = pixel;
-                        was_colored = true;
-                    }
-                    129 => {
-                        print!("{}{}", color::Bg(color::LightRed), color::Fg(color::Black));
-                        print!("{}", CHAR_PIERCING0);
-                        last_pixel = pixel;
-                        was_colored = true;
-                    }
-                    130 => {
-                        print!("{}{}", color::Bg(color::LightRed), color::Fg(color::Black));
-                        print!("{}", CHAR_PIERCING1);
-                        last_pixel = pixel;
-                        was_colored = true;
                     }
-                    _ => {
-                        last_char = self.fill_color(pixel - 1, last_pixel - 1, last_char);
-                        last_pixel = pixel;
-                        was_colored = true;
+                    if let Some((palette, luma)) = glow {
+                        self.brighten(y * width + x, palette, luma, luma);
                     }
                 }
             }
+        }
+
+        let (palette, flash) = camera.flash;
+        if flash > 0.0 {
+            for i in 0..self.bitmap.len() {
+                self.brighten(i, palette, flash * FLASH_EMPTY, flash * FLASH_SHAPE);
+            }
+        }
+    }
+
+    fn invalidate(&mut self) {
+        self.redraw = true;
+    }
+
+    fn display(&mut self, out: &mut dyn Write) -> io::Result<()> {
+        let frame = self.resolve_frame();
+        if self.last_frame.len() != frame.len() {
+            self.redraw = true;
+        }
+
+        let width = self.size.0 as usize;
+        self.buffer.clear();
+        let mut cursor: Option<usize> = None;
+        let mut pen: Option<(TermColor, TermColor, TextStyle)> = None;
+
+        for (i, cell) in frame.iter().enumerate() {
+            if !self.redraw && self.last_frame[i] == *cell {
+                continue;
+            }
+            //already covered by the wide character on its left
+            if cell.chr == WIDE_CONTINUATION {
+                continue;
+            }
+
+            //jump only when the changed cells are not contiguous on the same row
+            if cursor != Some(i) || i % width == 0 {
+                let goto = termion::cursor::Goto((i % width) as u16 + 1, (i / width) as u16 + 1);
+                write!(self.buffer, "{}", goto)?;
+            }
 
-            if was_colored {
-                print!("{}{}", color::Bg(color::Black), color::Fg(color::White));
+            let (mut last_bg, mut last_fg, last_style) = match pen {
+                Some((bg, fg, style)) => (Some(bg), Some(fg), Some(style)),
+                None => (None, None, None),
+            };
+            //resetting the style resets the colors too
+            if last_style != Some(cell.style) {
+                write_style(&mut self.buffer, cell.style)?;
+                last_bg = None;
+                last_fg = None;
+            }
+            if last_bg != Some(cell.bg) {
+                write_bg(&mut self.buffer, cell.bg, self.color_mode)?;
             }
+            if last_fg != Some(cell.fg) {
+                write_fg(&mut self.buffer, cell.fg, self.color_mode)?;
+            }
+            write!(self.buffer, "{}", cell.chr)?;
 
-            print!("\r");
+            let wide = i % width + 1 < width && frame[i + 1].chr == WIDE_CONTINUATION;
+            cursor = Some(i + 1 + wide as usize);
+            pen = Some((cell.bg, cell.fg, cell.style));
         }
+
+        if let Some((bg, fg, style)) = pen {
+            if style != PLAIN_STYLE {
+                write_style(&mut self.buffer, PLAIN_STYLE)?;
+            }
+            if style != PLAIN_STYLE || (bg, fg) != (self.empty.bg, self.empty.fg) {
+                write_bg(&mut self.buffer, self.empty.bg, self.color_mode)?;
+                write_fg(&mut self.buffer, self.empty.fg, self.color_mode)?;
+            }
+        }
+
+        self.frame_bytes = self.buffer.len();
+        self.last_frame = frame;
+        self.redraw = false;
+
+        out.write_all(&self.buffer)?;
+        out.flush()
+    }
+}
+
+//a reset followed by the attributes that are on
+fn write_style(output: &mut Vec<u8>, style: TextStyle) -> io::Result<()> {
+    write!(output, "{}", termion::style::Reset)?;
+    if style.bold {
+        write!(output, "{}", termion::style::Bold)?;
+    }
+    if style.underline {
+        write!(output, "{}", termion::style::Underline)?;
+    }
+    Ok(())
+}
+
+//colors are downgraded to what the terminal supports, or left out
+pub fn write_bg(output: &mut Vec<u8>, c: TermColor, mode: ColorMode) -> io::Result<()> {
+    match (mode, c) {
+        (ColorMode::NoColor, _) => Ok(()),
+        (ColorMode::Ansi16, c) => match ansi16(c) {
+            v @ 0..=7 => write!(output, "\x1b[{}m", 40 + v),
+            v => write!(output, "\x1b[{}m", 100 + v - 8),
+        },
+        (ColorMode::Palette256, c) => {
+            write!(output, "{}", color::Bg(color::AnsiValue(xterm256(c))))
+        }
+        (_, TermColor::Indexed(v)) => write!(output, "{}", color::Bg(color::AnsiValue(v))),
+        (_, TermColor::Rgb((r, g, b))) => write!(output, "{}", color::Bg(color::Rgb(r, g, b))),
+    }
+}
+
+pub fn write_fg(output: &mut Vec<u8>, c: TermColor, mode: ColorMode) -> io::Result<()> {
+    match (mode, c) {
+        (ColorMode::NoColor, _) => Ok(()),
+        (ColorMode::Ansi16, c) => match ansi16(c) {
+            v @ 0..=7 => write!(output, "\x1b[{}m", 30 + v),
+            v => write!(output, "\x1b[{}m", 90 + v - 8),
+        },
+        (ColorMode::Palette256, c) => {
+            write!(output, "{}", color::Fg(color::AnsiValue(xterm256(c))))
+        }
+        (_, TermColor::Indexed(v)) => write!(output, "{}", color::Fg(color::AnsiValue(v))),
+        (_, TermColor::Rgb((r, g, b))) => write!(output, "{}", color::Fg(color::Rgb(r, g, b))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headlesscontext::HeadlessContext;
+    use unicode_width::UnicodeWidthChar;
+
+    #[test]
+    fn overlapping_wide_text_keeps_pairs_whole() {
+        let mut ctx = HeadlessContext::new((20, 3));
+        for string in ["x\u{65e5}y", "\u{65e5}\u{672c}\u{8a9e}"] {
+            ctx.add_text_entry(&TextEntry {
+                position: (8.0, 1.0),
+                string: String::from(string),
+                ..TextEntry::empty_text_entry()
+            });
+        }
+        ctx.display(&mut io::sink()).unwrap();
+
+        let row = &ctx.cells()[20..40];
+        for (x, cell) in row.iter().enumerate() {
+            let wide = cell.chr.width() == Some(2);
+            let continued = x + 1 < row.len() && row[x + 1].chr == WIDE_CONTINUATION;
+            assert_eq!(wide, continued, "wide pair broken at column {}", x);
+        }
+        assert_eq!(
+            ctx.to_text().lines().nth(1).unwrap().trim_end(),
+            "        \u{65e5}\u{672c}\u{8a9e}"
+        );
+    }
+
+    #[test]
+    fn rgb_is_indexed_in_256_color_mode() {
+        let mut output = Vec::new();
+        write_fg(
+            &mut output,
+            TermColor::Rgb((255, 0, 0)),
+            ColorMode::Palette256,
+        )
+        .unwrap();
+        write_bg(
+            &mut output,
+            TermColor::Rgb((30, 30, 30)),
+            ColorMode::Palette256,
+        )
+        .unwrap();
+        write_bg(&mut output, TermColor::Indexed(9), ColorMode::Palette256).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\x1b[38;5;196m\x1b[48;5;234m\x1b[48;5;9m"
+        );
     }
 }
@@ -1,4 +1,3 @@
-use crate::asciicontext::AsciiContext;
 use crate::drawables::*;
 use crate::drawingcontext::DrawingContext;
 use crate::enemy::EnemyType;
@@ -28,7 +27,7 @@ pub struct Asteroid {
 }
 
 impl TerminalDrawble for Asteroid {
-    fn draw(&self, ctx: &mut AsciiContext) {
+    fn draw(&self, ctx: &mut dyn DrawingContext) {
         let (sides, radius) = self.get_description();
         let mut triangles = vec![EMPTY_TRIANGLE; sides];
 
@@ -51,10 +50,10 @@ impl TerminalDrawble for Asteroid {
             triangles[i] = Triangle {
                 points: [self.position, point_left, point_right],
                 colors: [0.55, 0.2, 0.3],
-                color_palette: self.color_palette.clone(),
+                color_palettes: [self.color_palette.clone(); 3],
             };
         }
-        ctx.add_triangles(&triangles);
+        ctx.add_shape(&triangles);
     }
 }
 
@@ -89,6 +88,10 @@ impl Sprite for Asteroid {
         }
     }
 
+    fn clamp(&mut self, bounds: Vec2) {
+        self.position = clamp_position(self.position, bounds);
+    }
+
     fn is_alive(&self) -> bool {
         return true;
     }
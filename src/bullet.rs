@@ -1,4 +1,3 @@
-use crate::asciicontext::AsciiContext;
 use crate::drawables::*;
 use crate::drawingcontext::DrawingContext;
 use crate::sprite::*;
@@ -38,13 +37,13 @@ impl Bullet {
     pub fn get_drawable_point(&self) -> Point {
         Point {
             position: self.position,
-            color: match self.bullet_type {
-                BulletType::Normal => 128.0,
-                BulletType::Piercing => 129.0 + (self.life * 30.0).rem_euclid(2.0).floor(),
-            },
+            color: 1.0,
             color_palette: match self.bullet_type {
-                BulletType::Normal => ColorPalette::Custom,
-                BulletType::Piercing => ColorPalette::Custom,
+                BulletType::Normal => ColorPalette::Symbol(Symbol::Ball),
+                BulletType::Piercing => match (self.life * 30.0).rem_euclid(2.0) < 1.0 {
+                    true => ColorPalette::Symbol(Symbol::Piercing0),
+                    false => ColorPalette::Symbol(Symbol::Piercing1),
+                },
             },
         }
     }
@@ -80,13 +79,17 @@ impl Sprite for Bullet {
         self.life -= delta;
     }
 
+    fn clamp(&mut self, bounds: Vec2) {
+        self.position = clamp_position(self.position, bounds);
+    }
+
     fn is_alive(&self) -> bool {
         self.life > 0.0
     }
 }
 
 impl TerminalDrawble for Bullet {
-    fn draw(&self, ctx: &mut AsciiContext) {
+    fn draw(&self, ctx: &mut dyn DrawingContext) {
         ctx.add_point(&self.get_drawable_point());
     }
 }
@@ -111,13 +114,19 @@ impl Sprite for Bullets {
         self.bullets.retain(|bullet| bullet.is_alive());
     }
 
+    fn clamp(&mut self, bounds: Vec2) {
+        self.bullets
+            .iter_mut()
+            .for_each(|bullet| bullet.clamp(bounds));
+    }
+
     fn is_alive(&self) -> bool {
         return true;
     }
 }
 
 impl TerminalDrawble for Bullets {
-    fn draw(&self, ctx: &mut AsciiContext) {
+    fn draw(&self, ctx: &mut dyn DrawingContext) {
         let points = self
             .bullets
             .iter()
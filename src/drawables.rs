@@ -1,3 +1,6 @@
+use rand::Rng;
+use unicode_width::UnicodeWidthStr;
+
 /*
 pub const DEFAULT_COLOR: (&str, &str) = (color::Black.bg_str(), color::White.fg_str());
 pub const RED_PALETTE: [(&str, &str); 5] = [
@@ -33,74 +36,217 @@ pub const GRAY_PALETTE: [(&str, &str); 3] = [
     (color::White.bg_str(), color::LightWhite.fg_str()),
 ];
 */
-pub const CHAR_BALL: char = '\u{25CF}';
-pub const CHAR_PIERCING0: char = '8';
-pub const CHAR_PIERCING1: char = '\u{221E}';
-pub const CHARS_GRADIENT: [char; 5] = [' ', '\u{2591}', '\u{2592}', '\u{2593}', '\u{2593}'];
-//pub const CHARS_GRADIENT: [char; 5] = ['.', 'x', '%', '#', '@'];
-pub const DEFAULT_COLOR: (&str, &str) = ("\u{1b}[48;5;0m", "\u{1b}[38;5;7m");
+//visible world height in units at zoom 1.0
+pub const WORLD_HEIGHT: f32 = 63.0;
+//height over width of a terminal cell, used when the terminal can't tell us
+pub const DEFAULT_CELL_ASPECT: f32 = 17.0 / 8.0;
+//trauma lost per second
+const TRAUMA_DECAY: f32 = 1.5;
+//shake at full trauma, in world units and radians
+const SHAKE_OFFSET: f32 = 1.5;
+const SHAKE_ANGLE: f32 = 0.05;
+//flash intensity lost per second
+const FLASH_DECAY: f32 = 3.0;
+pub const CHAR_HALF_TOP: char = '\u{2580}';
+pub const CHAR_HALF_BOTTOM: char = '\u{2584}';
+pub const CHAR_FULL: char = '\u{2588}';
+pub const CHAR_BRAILLE: u32 = 0x2800;
+//bit of every dot inside a braille character, indexed by [y][x]
+pub const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+//(background, foreground) 256-color indexes until a theme says otherwise
+pub const DEFAULT_COLOR: (u8, u8) = (0, 7);
 pub const PALETTE_RANGE: u8 = 16;
+//ordered dither thresholds, spread evenly over one quantization step
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
 
-pub const FIRE_PALETTE: [(&str, &str); 7] = [
-    ("\u{1b}[48;5;0m", "\u{1b}[38;5;1m"),
-    ("\u{1b}[48;5;1m", "\u{1b}[38;5;9m"),
-    ("\u{1b}[48;5;9m", "\u{1b}[38;5;11m"),
-    ("\u{1b}[48;5;11m", "\u{1b}[38;5;11m"),
-    ("\u{1b}[48;5;11m", "\u{1b}[38;5;11m"),
-    ("\u{1b}[48;5;11m", "\u{1b}[38;5;15m"),
-    ("\u{1b}[48;5;15m", "\u{1b}[38;5;15m"),
+//xterm default values for the first 16 colors
+pub const ANSI_RGB: [Rgb; 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
 ];
 
-pub const RED_PALETTE: [(&str, &str); 3] = [
-    ("\u{1b}[48;5;0m", "\u{1b}[38;5;1m"),
-    ("\u{1b}[48;5;1m", "\u{1b}[38;5;9m"),
-    ("\u{1b}[48;5;9m", "\u{1b}[38;5;11m"),
-];
+pub type Vec2 = (f32, f32);
+pub type ColorLuma = f32;
+pub type Rgb = (u8, u8, u8);
 
-pub const GREEN_PALETTE: [(&str, &str); 3] = [
-    ("\u{1b}[48;5;0m", "\u{1b}[38;5;2m"),
-    ("\u{1b}[48;5;2m", "\u{1b}[38;5;10m"),
-    ("\u{1b}[48;5;10m", "\u{1b}[38;5;11m"),
-];
+pub fn distance(a: Vec2, b: Vec2) -> f32 {
+    f32::sqrt((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2))
+}
 
-pub const BLUE_PALETTE: [(&str, &str); 3] = [
-    ("\u{1b}[48;5;0m", "\u{1b}[38;5;4m"),
-    ("\u{1b}[48;5;4m", "\u{1b}[38;5;12m"),
-    ("\u{1b}[48;5;12m", "\u{1b}[38;5;14m"),
-];
+pub fn length(a: Vec2) -> f32 {
+    f32::sqrt(a.0 * a.0 + a.1 * a.1)
+}
 
-pub const YELLOW_PALETTE: [(&str, &str); 3] = [
-    ("\u{1b}[48;5;8m", "\u{1b}[38;5;3m"),
-    ("\u{1b}[48;5;3m", "\u{1b}[38;5;11m"),
-    ("\u{1b}[48;5;11m", "\u{1b}[38;5;15m"),
-];
+//threshold of a pixel in the ordered dither matrix, between 0 and 1
+pub fn bayer(x: usize, y: usize) -> f32 {
+    (BAYER_4X4[y % 4][x % 4] as f32 + 0.5) / 16.0
+}
 
-pub const MAGENTA_PALETTE: [(&str, &str); 2] = [
-    ("\u{1b}[48;5;0m", "\u{1b}[38;5;5m"),
-    ("\u{1b}[48;5;5m", "\u{1b}[38;5;13m"),
-];
+//a world position moved inside the (-bounds, bounds) box
+pub fn clamp_position(position: Vec2, bounds: Vec2) -> Vec2 {
+    (
+        position.0.clamp(-bounds.0, bounds.0),
+        position.1.clamp(-bounds.1, bounds.1),
+    )
+}
 
-pub const CYAN_PALETTE: [(&str, &str); 3] = [
-    ("\u{1b}[48;5;0m", "\u{1b}[38;5;6m"),
-    ("\u{1b}[48;5;6m", "\u{1b}[38;5;14m"),
-    ("\u{1b}[48;5;14m", "\u{1b}[38;5;15m"),
-];
+pub fn gradient_rgb(stops: &[u8], luma: ColorLuma) -> Rgb {
+    let t = luma.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let i = (t as usize).min(stops.len() - 2);
+    let f = t - i as f32;
+    let a = ANSI_RGB[stops[i] as usize];
+    let b = ANSI_RGB[stops[i + 1] as usize];
+    (
+        (a.0 as f32 + (b.0 as f32 - a.0 as f32) * f) as u8,
+        (a.1 as f32 + (b.1 as f32 - a.1 as f32) * f) as u8,
+        (a.2 as f32 + (b.2 as f32 - a.2 as f32) * f) as u8,
+    )
+}
 
-pub const GRAY_PALETTE: [(&str, &str); 3] = [
-    ("\u{1b}[48;5;0m", "\u{1b}[38;5;8m"),
-    ("\u{1b}[48;5;8m", "\u{1b}[38;5;7m"),
-    ("\u{1b}[48;5;7m", "\u{1b}[38;5;15m"),
-];
+//closest of the first 16 colors, for terminals that can't show more
+pub fn ansi16(color: TermColor) -> u8 {
+    let rgb = match color {
+        TermColor::Indexed(v) if v < 16 => return v,
+        TermColor::Indexed(v) => xterm_rgb(v),
+        TermColor::Rgb(rgb) => rgb,
+    };
+    let distance = |c: &Rgb| {
+        (c.0 as i32 - rgb.0 as i32).pow(2)
+            + (c.1 as i32 - rgb.1 as i32).pow(2)
+            + (c.2 as i32 - rgb.2 as i32).pow(2)
+    };
+    (0..ANSI_RGB.len())
+        .min_by_key(|&i| distance(&ANSI_RGB[i]))
+        .unwrap_or(0) as u8
+}
 
-pub type Vec2 = (f32, f32);
-pub type ColorLuma = f32;
+//closest 256-color index, searched in the cube and the gray ramp
+//the first 16 colors are left out since every terminal theme changes them
+pub fn xterm256(color: TermColor) -> u8 {
+    let rgb = match color {
+        TermColor::Indexed(v) => return v,
+        TermColor::Rgb(rgb) => rgb,
+    };
+    let distance = |c: Rgb| {
+        (c.0 as i32 - rgb.0 as i32).pow(2)
+            + (c.1 as i32 - rgb.1 as i32).pow(2)
+            + (c.2 as i32 - rgb.2 as i32).pow(2)
+    };
+    (16..=255u8)
+        .min_by_key(|&v| distance(xterm_rgb(v)))
+        .unwrap_or(16)
+}
 
-pub fn distance(a: Vec2, b: Vec2) -> f32 {
-    f32::sqrt((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2))
+//the 6x6x6 color cube and the gray ramp above the first 16 colors
+fn xterm_rgb(v: u8) -> Rgb {
+    match v {
+        0..=15 => ANSI_RGB[v as usize],
+        16..=231 => {
+            let level = |l: u8| if l == 0 { 0 } else { 55 + l * 40 };
+            let i = v - 16;
+            (level(i / 36), level(i / 6 % 6), level(i % 6))
+        }
+        _ => {
+            let gray = 8 + (v - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
 }
 
-pub fn length(a: Vec2) -> f32 {
-    f32::sqrt(a.0 * a.0 + a.1 * a.1)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TermColor {
+    Indexed(u8),
+    Rgb(Rgb),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorMode {
+    Palette256,
+    TrueColor,
+    Ansi16,
+    //shading is done with glyphs alone
+    NoColor,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RenderMode {
+    Cell,
+    HalfBlock,
+    Braille,
+}
+
+impl RenderMode {
+    pub fn from_name(name: &str) -> Option<RenderMode> {
+        match name {
+            "cell" => Some(RenderMode::Cell),
+            "half-block" => Some(RenderMode::HalfBlock),
+            "braille" => Some(RenderMode::Braille),
+            _ => None,
+        }
+    }
+
+    //how many rasterizer pixels fit inside one terminal cell
+    pub fn sub_size(&self) -> (u16, u16) {
+        match self {
+            RenderMode::Cell => (1, 1),
+            RenderMode::HalfBlock => (1, 2),
+            RenderMode::Braille => (2, 4),
+        }
+    }
+}
+
+impl ColorMode {
+    pub fn detect() -> ColorMode {
+        let var = |name: &str| std::env::var(name).unwrap_or_default().to_lowercase();
+        ColorMode::from_env(&var("NO_COLOR"), &var("COLORTERM"), &var("TERM"))
+    }
+
+    //NO_COLOR wins, then COLORTERM, then what TERM is known to support
+    pub fn from_env(no_color: &str, colorterm: &str, term: &str) -> ColorMode {
+        if !no_color.is_empty() {
+            return ColorMode::NoColor;
+        }
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorMode::TrueColor;
+        }
+        match term {
+            "" | "dumb" => ColorMode::NoColor,
+            "linux" | "ansi" | "cons25" => ColorMode::Ansi16,
+            //real dec terminals like vt100 or vt220, not vte based ones
+            term if term
+                .strip_prefix("vt")
+                .is_some_and(|model| model.starts_with(|c: char| c.is_ascii_digit())) =>
+            {
+                ColorMode::NoColor
+            }
+            term if term.ends_with("-16color") => ColorMode::Ansi16,
+            _ => ColorMode::Palette256,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ColorMode> {
+        match name {
+            "truecolor" => Some(ColorMode::TrueColor),
+            "256" => Some(ColorMode::Palette256),
+            "16" => Some(ColorMode::Ansi16),
+            "none" => Some(ColorMode::NoColor),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -108,6 +254,26 @@ pub struct Camera {
     pub position: Vec2,
     pub size: Vec2,
     pub zoom: f32,
+    pub cell_aspect: f32,
+    pub rotation: f32,
+    //0.0 to 1.0, decays over time, the shake grows with the square of it
+    pub trauma: f32,
+    shake: (f32, f32, f32),
+    //full screen tint, intensity from 0.0 to 1.0 decays over time
+    pub flash: (ColorPalette, f32),
+    //no shake and no flashes
+    pub reduced_motion: bool,
+}
+
+//higher layers are not overwritten by lower ones
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Layer {
+    Empty,
+    //trail and bloom glow, text on this layer goes over the glow but under any shape
+    Background,
+    Shape,
+    Symbol,
+    Text,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -115,9 +281,30 @@ pub enum TextColorPalette {
     Text,
     Menu,
     Warning,
+    //background and foreground
+    Custom(TermColor, TermColor),
 }
 
-#[derive(Clone, Copy, Debug)]
+//how position.0 of a text entry is read, as its left edge, middle or right edge
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextStyle {
+    pub bold: bool,
+    pub underline: bool,
+}
+
+pub const PLAIN_STYLE: TextStyle = TextStyle {
+    bold: false,
+    underline: false,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ColorPalette {
     Red,
     Green,
@@ -126,7 +313,15 @@ pub enum ColorPalette {
     Magenta,
     Cyan,
     Gray,
-    Custom,
+    //a fixed glyph with its own colors instead of a shaded palette
+    Symbol(Symbol),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Symbol {
+    Ball,
+    Piercing0,
+    Piercing1,
 }
 
 #[derive(Clone, Debug)]
@@ -134,13 +329,23 @@ pub struct TextEntry {
     pub position: Vec2,
     pub string: String,
     pub color_palette: TextColorPalette,
+    pub layer: Layer,
+    pub align: TextAlign,
+    pub style: TextStyle,
 }
 
 #[derive(Clone, Debug)]
 pub struct Triangle {
     pub points: [Vec2; 3],
     pub colors: [ColorLuma; 3],
-    pub color_palette: ColorPalette,
+    pub color_palettes: [ColorPalette; 3],
+}
+
+#[derive(Clone, Debug)]
+pub struct Line {
+    pub points: [Vec2; 2],
+    pub colors: [ColorLuma; 2],
+    pub color_palettes: [ColorPalette; 2],
 }
 
 #[derive(Clone, Debug)]
@@ -156,6 +361,23 @@ impl TextEntry {
             position: (0.0, 0.0),
             string: String::from(""),
             color_palette: TextColorPalette::Text,
+            layer: Layer::Text,
+            align: TextAlign::Left,
+            style: PLAIN_STYLE,
+        }
+    }
+
+    //terminal columns taken by the string, wide characters count twice
+    pub fn width(&self) -> usize {
+        self.string.width()
+    }
+
+    //column of the first character once the alignment is applied
+    pub fn left(&self) -> f32 {
+        match self.align {
+            TextAlign::Left => self.position.0,
+            TextAlign::Center => (self.position.0 - self.width() as f32 / 2.0).floor(),
+            TextAlign::Right => self.position.0 - self.width() as f32,
         }
     }
 }
@@ -169,17 +391,163 @@ pub const EMPTY_POINT: Point = Point {
 pub const EMPTY_TRIANGLE: Triangle = Triangle {
     points: [(0.0, 0.0), (0.0, 0.0), (0.0, 0.0)],
     colors: [0.0, 0.0, 0.0],
-    color_palette: ColorPalette::Red,
+    color_palettes: [ColorPalette::Red; 3],
+};
+
+pub const EMPTY_LINE: Line = Line {
+    points: [(0.0, 0.0), (0.0, 0.0)],
+    colors: [0.0, 0.0],
+    color_palettes: [ColorPalette::Red; 2],
 };
 
+//edges of a triangle mesh that belong to a single triangle, the inner fan edges are dropped
+pub fn outline(triangles: &[Triangle]) -> Vec<Line> {
+    let same = |a: Vec2, b: Vec2| (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3;
+    let edges: Vec<([Vec2; 2], [ColorPalette; 2])> = triangles
+        .iter()
+        .flat_map(|tri| {
+            [(0, 1), (1, 2), (2, 0)].map(|(a, b)| {
+                (
+                    [tri.points[a], tri.points[b]],
+                    [tri.color_palettes[a], tri.color_palettes[b]],
+                )
+            })
+        })
+        .collect();
+
+    edges
+        .iter()
+        .filter(|([a, b], _)| {
+            let shared = edges
+                .iter()
+                .filter(|([c, d], _)| {
+                    (same(*a, *c) && same(*b, *d)) || (same(*a, *d) && same(*b, *c))
+                })
+                .count();
+            shared == 1 && !same(*a, *b)
+        })
+        .map(|(points, color_palettes)| Line {
+            points: *points,
+            colors: [1.0, 1.0],
+            color_palettes: *color_palettes,
+        })
+        .collect()
+}
+
 impl Camera {
+    pub fn new(size: (u16, u16), zoom: f32, cell_aspect: f32) -> Camera {
+        Camera {
+            position: (0.0, 0.0),
+            size: (size.0 as f32, size.1 as f32),
+            zoom,
+            cell_aspect,
+            rotation: 0.0,
+            trauma: 0.0,
+            shake: (0.0, 0.0, 0.0),
+            flash: (ColorPalette::Gray, 0.0),
+            reduced_motion: false,
+        }
+    }
+
+    pub fn resize(&mut self, size: (u16, u16)) {
+        self.size = (size.0 as f32, size.1 as f32);
+    }
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        if self.reduced_motion {
+            return;
+        }
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    //the strongest flash wins the color
+    pub fn add_flash(&mut self, palette: ColorPalette, amount: f32) {
+        if self.reduced_motion || amount < self.flash.1 {
+            return;
+        }
+        self.flash = (palette, amount.clamp(0.0, 1.0));
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.trauma = (self.trauma - TRAUMA_DECAY * delta).max(0.0);
+        self.flash.1 = (self.flash.1 - FLASH_DECAY * delta).max(0.0);
+
+        let shake = self.trauma * self.trauma;
+        if shake > 0.0 {
+            let mut rnd = rand::thread_rng();
+            self.shake = (
+                shake * SHAKE_OFFSET * rnd.gen_range(-1.0..1.0),
+                shake * SHAKE_OFFSET * rnd.gen_range(-1.0..1.0),
+                shake * SHAKE_ANGLE * rnd.gen_range(-1.0..1.0),
+            );
+        } else {
+            self.shake = (0.0, 0.0, 0.0);
+        }
+    }
+
+    //terminal cells per world unit, the vertex shader and the bounds both go through this
+    pub fn get_scale(&self) -> (f32, f32) {
+        let default_height: f32 = self.size.1 / WORLD_HEIGHT;
+        (
+            default_height * self.zoom * self.cell_aspect,
+            default_height * self.zoom,
+        )
+    }
+
+    //world point in the middle of the screen, shake included
+    pub fn get_center(&self) -> Vec2 {
+        (
+            self.position.0 + self.shake.0,
+            self.position.1 + self.shake.1,
+        )
+    }
+
+    pub fn get_angle(&self) -> f32 {
+        self.rotation + self.shake.2
+    }
+
+    //half size of the world, it wraps around there no matter where the camera looks
     pub fn get_bounds(&self) -> (f32, f32) {
-        let _aspect_ratio: (f32, f32) = (1.0, 1.0); //camera.size.1 / camera.size.0, 1.0);
-        let char_ratio: (f32, f32) = (17.0 / 8.0, 1.0);
-        let default_height: f32 = self.size.1 as f32 / 63.0;
+        let scale = self.get_scale();
+        (self.size.0 / scale.0 / 2.0, self.size.1 / scale.1 / 2.0)
+    }
+
+    //world space box around everything the camera can see, as (min, max)
+    pub fn get_view(&self) -> (Vec2, Vec2) {
+        let half = self.get_bounds();
+        let (sin, cos) = self.get_angle().sin_cos();
+        let extent = (
+            half.0 * cos.abs() + half.1 * sin.abs(),
+            half.0 * sin.abs() + half.1 * cos.abs(),
+        );
+        let center = self.get_center();
         (
-            self.size.0 / default_height / self.zoom / char_ratio.0 / 2.0,
-            self.size.1 / default_height / self.zoom / char_ratio.1 / 2.0,
+            (center.0 - extent.0, center.1 - extent.1),
+            (center.0 + extent.0, center.1 + extent.1),
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_mode_from_env() {
+        assert_eq!(
+            ColorMode::from_env("", "", "vte-256color"),
+            ColorMode::Palette256
+        );
+        assert_eq!(ColorMode::from_env("", "", "vte"), ColorMode::Palette256);
+        assert_eq!(ColorMode::from_env("", "", "linux"), ColorMode::Ansi16);
+        assert_eq!(ColorMode::from_env("", "", "vt100"), ColorMode::NoColor);
+        assert_eq!(
+            ColorMode::from_env("", "", "vt220-8bit"),
+            ColorMode::NoColor
+        );
+        assert_eq!(
+            ColorMode::from_env("1", "truecolor", "xterm-256color"),
+            ColorMode::NoColor
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::drawables::*;
+use std::io::{self, Write};
 
 pub trait DrawingContext {
     fn resize(&mut self, size: (u16, u16));
@@ -6,9 +7,27 @@ pub trait DrawingContext {
     fn flush_text_entries(&mut self);
     fn flush_triangles(&mut self);
     fn flush_points(&mut self);
+    fn flush_lines(&mut self);
     fn add_text_entry(&mut self, text_entry: &TextEntry);
     fn add_triangles(&mut self, triangles: &Vec<Triangle>);
     fn add_point(&mut self, point: &Point);
     fn add_points(&mut self, points: &Vec<Point>);
-    fn display(&self);
+    fn add_lines(&mut self, lines: &[Line]);
+    fn draw_triangles(&mut self, camera: &Camera);
+    fn draw_points(&mut self, camera: &Camera);
+    fn draw_lines(&mut self, camera: &Camera);
+    fn vector(&self) -> bool;
+    fn persist(&mut self, delta: f32);
+    fn effects(&mut self, camera: &Camera);
+    fn invalidate(&mut self);
+    fn display(&mut self, out: &mut dyn Write) -> io::Result<()>;
+
+    //filled triangles, or only their outline in vector mode
+    fn add_shape(&mut self, triangles: &[Triangle]) {
+        if self.vector() {
+            self.add_lines(&outline(triangles));
+            return;
+        }
+        self.add_triangles(&triangles.to_vec());
+    }
 }
@@ -1,7 +1,7 @@
-use crate::asciicontext::AsciiContext;
 use crate::asteroid::*;
 use crate::bullet::*;
 use crate::drawables::*;
+use crate::drawingcontext::DrawingContext;
 use crate::particle::*;
 use crate::powerup::Powerup;
 use crate::ship::*;
@@ -150,18 +150,27 @@ impl Enemies {
         return position;
     }
 
-    pub fn collide_with_ship(&mut self, camera: &Camera, ship: &mut Ship) {
+    pub fn collide_with_ship(&mut self, camera: &mut Camera, ship: &mut Ship) {
         self.enemies.iter().for_each(|enemy| {
             if enemy.collide_with_ship(ship) {
                 match enemy {
                     EnemyType::Powerup(_) => {}
-                    _ => ship.damage(self.get_empty_point(camera), false),
+                    _ => {
+                        ship.damage(self.get_empty_point(camera), false);
+                        camera.add_trauma(0.8);
+                        camera.add_flash(ColorPalette::Red, 0.6);
+                    }
                 }
             }
         });
     }
 
-    pub fn collide_with_bullets(&mut self, bullets: &mut Bullets, ship: &mut Ship) {
+    pub fn collide_with_bullets(
+        &mut self,
+        camera: &mut Camera,
+        bullets: &mut Bullets,
+        ship: &mut Ship,
+    ) {
         let mut new_objects: Vec<EnemyType> = Vec::<EnemyType>::with_capacity(20);
         let damaged: Vec<usize> = self.damage(&self.enemies, bullets);
         damaged.iter().for_each(|&i| {
@@ -170,9 +179,18 @@ impl Enemies {
                 .for_each(|obj| new_objects.push(obj.clone()));
         });
         damaged.iter().rev().for_each(|&i| {
+            //big rocks breaking apart shake the screen
+            if let EnemyType::Asteroid(a) = self.enemies[i] {
+                match a.size {
+                    AsteroidSize::Huge => camera.add_trauma(0.5),
+                    AsteroidSize::Big => camera.add_trauma(0.3),
+                    _ => {}
+                }
+            }
             ship.score += match self.enemies[i] {
                 EnemyType::Powerup(p) => {
                     ship.powerup(&p);
+                    camera.add_flash(p.get_palette(), 0.4);
                     1000
                 }
                 EnemyType::Asteroid(a) => match a.size {
@@ -228,7 +246,7 @@ impl Enemies {
         damaged
     }
 
-    pub fn draw_particles(&self, ctx: &mut AsciiContext) {
+    pub fn draw_particles(&self, ctx: &mut dyn DrawingContext) {
         self.particles
             .iter()
             .for_each(|particle| particle.draw(ctx));
@@ -236,7 +254,7 @@ impl Enemies {
 }
 
 impl TerminalDrawble for Enemies {
-    fn draw(&self, ctx: &mut AsciiContext) {
+    fn draw(&self, ctx: &mut dyn DrawingContext) {
         self.enemies.iter().for_each(|obj| match obj {
             EnemyType::Asteroid(a) => a.draw(ctx),
             EnemyType::StarShip(s) => s.draw(ctx),
@@ -267,6 +285,17 @@ impl Sprite for Enemies {
         });
     }
 
+    fn clamp(&mut self, bounds: Vec2) {
+        self.particles
+            .iter_mut()
+            .for_each(|particle| particle.clamp(bounds));
+        self.enemies.iter_mut().for_each(|obj| match obj {
+            EnemyType::Asteroid(a) => a.clamp(bounds),
+            EnemyType::StarShip(s) => s.clamp(bounds),
+            EnemyType::Powerup(p) => p.clamp(bounds),
+        });
+    }
+
     fn is_alive(&self) -> bool {
         return true;
     }
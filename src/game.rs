@@ -3,31 +3,35 @@ use crate::bullet::*;
 use crate::drawables::*;
 use crate::drawingcontext::DrawingContext;
 use crate::enemy::*;
+use crate::input::Input;
+use crate::layout::*;
 use crate::leaderboard::*;
+use crate::settings::Settings;
 use crate::ship::*;
 use crate::sprite::Sprite;
+use crate::terminal::*;
 use crate::terminaldrawable::TerminalDrawble;
-use std::io::Write;
 use std::{thread, time};
 use termion::event::Key;
-use termion::input::Keys;
+use termion::input::Events;
 use termion::raw::RawTerminal;
 use termion::terminal_size;
 use termion::AsyncReader;
 
-pub fn game(stdin: &mut Keys<AsyncReader>, stdout: &mut RawTerminal<std::io::Stdout>) {
-    let term_size = terminal_size().unwrap();
-    let mut scr: AsciiContext = AsciiContext::new(term_size);
+pub fn game(
+    stdin: &mut Events<AsyncReader>,
+    stdout: &mut RawTerminal<std::io::Stdout>,
+    settings: &Settings,
+) {
+    let mut term_size = terminal_size().unwrap();
+    let mut scr: AsciiContext = AsciiContext::new(term_size, settings);
 
     let frame_fps = 30;
     let frame_len = time::Duration::from_micros(1000000 / frame_fps);
     let mut delta_time: f32 = frame_len.as_micros() as f32 / 1000000.0;
 
-    let camera = Camera {
-        position: (0.0, 0.0),
-        size: (term_size.0 as f32, term_size.1 as f32),
-        zoom: 2.0,
-    };
+    let mut camera = Camera::new(term_size, 2.0, settings.cell_aspect);
+    camera.reduced_motion = settings.reduced_motion;
 
     let mut enemies: Enemies = Enemies::new();
 
@@ -36,26 +40,47 @@ pub fn game(stdin: &mut Keys<AsyncReader>, stdout: &mut RawTerminal<std::io::Std
 
     let mut paused = false;
     let mut paused_draw = false;
+    let mut show_stats = false;
 
     loop {
         let frame_start = time::Instant::now();
 
-        match stdin.next() {
-            Some(result) => match result {
-                Ok(key) => match key {
-                    Key::Ctrl('c') | Key::Char('q') | Key::Esc => {
-                        ship.damage((0.0, 0.0), true);
-                    }
-                    Key::Left => ship.turn_left(),
-                    Key::Right => ship.turn_right(),
-                    Key::Up => ship.thrust(),
-                    Key::Char(' ') => ship.fire(),
-                    Key::Char('p') | Key::Char('P') => paused = !paused,
-                    _ => {}
-                },
+        let input = Input::poll_gameplay(stdin);
+        //losing focus pauses the game, it is resumed by hand
+        if input.focus_lost {
+            paused = true;
+        }
+        for key in input.keys {
+            match key {
+                Key::Ctrl('c') | Key::Char('q') | Key::Esc => {
+                    ship.damage((0.0, 0.0), true);
+                }
+                Key::Left => ship.turn_left(),
+                Key::Right => ship.turn_right(),
+                Key::Up => ship.thrust(),
+                Key::Char(' ') => ship.fire(),
+                Key::Char('p') | Key::Char('P') => paused = !paused,
+                Key::F(3) => show_stats = !show_stats,
+                Key::Ctrl('z') => suspend(stdout),
                 _ => {}
-            },
-            _ => {}
+            }
+        }
+
+        let terminal = poll_terminal(&mut scr, stdout, &mut term_size);
+        if terminal.resized {
+            camera.resize(term_size);
+            ship.clamp(camera.get_bounds());
+            ship_bullets.clamp(camera.get_bounds());
+            enemies.clamp(camera.get_bounds());
+            paused_draw = false;
+        }
+        if terminal.resumed {
+            paused = true;
+            paused_draw = false;
+        }
+        if !terminal.fits {
+            thread::sleep(frame_len);
+            continue;
         }
 
         if !paused {
@@ -66,12 +91,13 @@ pub fn game(stdin: &mut Keys<AsyncReader>, stdout: &mut RawTerminal<std::io::Std
             }
 
             //update
+            camera.update(delta_time);
             ship.update(&camera, delta_time);
             ship.update_switches(&mut ship_bullets);
             ship_bullets.update(&camera, delta_time);
             enemies.update_with_ship(&camera, delta_time, &ship);
-            enemies.collide_with_bullets(&mut ship_bullets, &mut ship);
-            enemies.collide_with_ship(&camera, &mut ship);
+            enemies.collide_with_bullets(&mut camera, &mut ship_bullets, &mut ship);
+            enemies.collide_with_ship(&mut camera, &mut ship);
 
             //add the text
             scr.flush_text_entries();
@@ -82,14 +108,25 @@ pub fn game(stdin: &mut Keys<AsyncReader>, stdout: &mut RawTerminal<std::io::Std
                 position: (0.0, 0.0),
                 string: score_string,
                 color_palette: TextColorPalette::Menu,
+                ..TextEntry::empty_text_entry()
             });
             scr.add_text_entry(&TextEntry {
                 position: (0.0, 1.0),
                 string: life_string,
                 color_palette: TextColorPalette::Text,
+                ..TextEntry::empty_text_entry()
             });
+            if show_stats {
+                scr.add_text_entry(&TextEntry {
+                    position: (0.0, 2.0),
+                    string: format!("Frame : {} bytes", scr.frame_bytes()),
+                    color_palette: TextColorPalette::Text,
+                    ..TextEntry::empty_text_entry()
+                });
+            }
 
             scr.flush_triangles();
+            scr.flush_lines();
             scr.flush_points();
             scr.clear();
 
@@ -102,29 +139,33 @@ pub fn game(stdin: &mut Keys<AsyncReader>, stdout: &mut RawTerminal<std::io::Std
             ship_bullets.draw(&mut scr);
 
             scr.draw_triangles(&camera);
+            scr.draw_lines(&camera);
             scr.draw_points(&camera);
+            scr.persist(delta_time);
+            scr.effects(&camera);
 
-            scr.display();
-
-            stdout.flush().unwrap();
+            scr.display(stdout).unwrap();
         } else {
             if !paused_draw {
                 let messages = ["==============", ">>> PAUSED <<<", "=============="];
-                let message_x = (term_size.0 - messages[0].len() as u16) / 2;
-                let message_y = (term_size.1 - messages.len() as u16) / 2;
+                let message_y = ((term_size.1 as f32 - messages.len() as f32) / 2.0).floor();
 
                 scr.flush_text_entries();
                 for i in 0..messages.len() {
                     scr.add_text_entry(&TextEntry {
-                        position: (message_x as f32, message_y as f32 + i as f32),
+                        position: (term_size.0 as f32 / 2.0, message_y + i as f32),
                         string: String::from(messages[i]),
                         color_palette: TextColorPalette::Warning,
+                        align: TextAlign::Center,
+                        style: TextStyle {
+                            bold: true,
+                            underline: false,
+                        },
+                        ..TextEntry::empty_text_entry()
                     });
                 }
 
-                scr.display();
-
-                stdout.flush().unwrap();
+                scr.display(stdout).unwrap();
 
                 paused_draw = true;
             }
@@ -138,7 +179,7 @@ pub fn game(stdin: &mut Keys<AsyncReader>, stdout: &mut RawTerminal<std::io::Std
             time::Instant::now().duration_since(frame_start).as_micros() as f32 / 1000000.0;
     }
     if ship.score > 0 {
-        leaderboard_entry(stdin, stdout, ship.score);
+        leaderboard_entry(stdin, stdout, settings, ship.score);
     }
-    leaderboard(stdin, stdout);
+    leaderboard(stdin, stdout, settings);
 }
@@ -0,0 +1,267 @@
+use crate::asciicontext::*;
+use crate::drawables::*;
+use crate::drawingcontext::*;
+use crate::settings::Settings;
+use crate::theme::Theme;
+use std::io::{self, Write};
+
+//renders into memory instead of a terminal, used for snapshots of drawables
+pub struct HeadlessContext {
+    ascii: AsciiContext,
+    size: (u16, u16),
+    cells: Vec<ScreenCell>,
+}
+
+impl HeadlessContext {
+    //nothing is probed from the terminal or the environment, snapshots come out the same anywhere
+    pub fn new(size: (u16, u16)) -> HeadlessContext {
+        let settings = Settings {
+            render_mode: RenderMode::Cell,
+            color_mode: ColorMode::Palette256,
+            cell_aspect: DEFAULT_CELL_ASPECT,
+            theme: Theme::default(),
+            vector: false,
+            dither: false,
+            trails: None,
+            reduced_motion: false,
+        };
+        HeadlessContext::with_settings(size, &settings)
+    }
+
+    pub fn with_settings(size: (u16, u16), settings: &Settings) -> HeadlessContext {
+        HeadlessContext {
+            ascii: AsciiContext::new(size, settings),
+            size,
+            cells: vec![EMPTY_CELL; size.0 as usize * size.1 as usize],
+        }
+    }
+
+    pub fn cells(&self) -> &[ScreenCell] {
+        &self.cells
+    }
+
+    pub fn to_text(&self) -> String {
+        self.cells
+            .chunks(self.size.0 as usize)
+            .map(|line| {
+                line.iter()
+                    .map(|cell| cell.chr)
+                    .filter(|&chr| chr != WIDE_CONTINUATION)
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    //same as to_text, with a {bg:fg} marker every time the style changes on a row
+    pub fn to_styled(&self) -> String {
+        self.cells
+            .chunks(self.size.0 as usize)
+            .map(|line| {
+                let mut row = String::with_capacity(line.len() * 2);
+                let mut style: Option<(TermColor, TermColor)> = None;
+                line.iter()
+                    .filter(|cell| cell.chr != WIDE_CONTINUATION)
+                    .for_each(|cell| {
+                        if style != Some((cell.bg, cell.fg)) {
+                            row.push_str(&format!(
+                                "{{{}:{}}}",
+                                color_name(cell.bg),
+                                color_name(cell.fg)
+                            ));
+                            style = Some((cell.bg, cell.fg));
+                        }
+                        row.push(cell.chr);
+                    });
+                row
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+fn color_name(color: TermColor) -> String {
+    match color {
+        TermColor::Indexed(v) => format!("{}", v),
+        TermColor::Rgb((r, g, b)) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+    }
+}
+
+impl DrawingContext for HeadlessContext {
+    fn resize(&mut self, size: (u16, u16)) {
+        self.ascii.resize(size);
+        self.size = size;
+        self.cells = vec![EMPTY_CELL; size.0 as usize * size.1 as usize];
+    }
+
+    fn clear(&mut self) {
+        self.ascii.clear();
+    }
+
+    fn flush_text_entries(&mut self) {
+        self.ascii.flush_text_entries();
+    }
+
+    fn flush_triangles(&mut self) {
+        self.ascii.flush_triangles();
+    }
+
+    fn flush_points(&mut self) {
+        self.ascii.flush_points();
+    }
+
+    fn flush_lines(&mut self) {
+        self.ascii.flush_lines();
+    }
+
+    fn add_text_entry(&mut self, text_entry: &TextEntry) {
+        self.ascii.add_text_entry(text_entry);
+    }
+
+    fn add_triangles(&mut self, triangles: &Vec<Triangle>) {
+        self.ascii.add_triangles(triangles);
+    }
+
+    fn add_point(&mut self, point: &Point) {
+        self.ascii.add_point(point);
+    }
+
+    fn add_points(&mut self, points: &Vec<Point>) {
+        self.ascii.add_points(points);
+    }
+
+    fn add_lines(&mut self, lines: &[Line]) {
+        self.ascii.add_lines(lines);
+    }
+
+    fn draw_triangles(&mut self, camera: &Camera) {
+        self.ascii.draw_triangles(camera);
+    }
+
+    fn draw_points(&mut self, camera: &Camera) {
+        self.ascii.draw_points(camera);
+    }
+
+    fn draw_lines(&mut self, camera: &Camera) {
+        self.ascii.draw_lines(camera);
+    }
+
+    fn vector(&self) -> bool {
+        self.ascii.vector()
+    }
+
+    fn persist(&mut self, delta: f32) {
+        self.ascii.persist(delta);
+    }
+
+    fn effects(&mut self, camera: &Camera) {
+        self.ascii.effects(camera);
+    }
+
+    fn invalidate(&mut self) {}
+
+    fn display(&mut self, out: &mut dyn Write) -> io::Result<()> {
+        self.cells = self.ascii.resolve_frame();
+        writeln!(out, "{}", self.to_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asteroid::*;
+    use crate::logo::*;
+    use crate::ship::Ship;
+    use crate::sprite::Sprite;
+    use crate::starship::*;
+    use crate::terminaldrawable::TerminalDrawble;
+    use std::fs;
+    use std::path::PathBuf;
+
+    const SIZE: (u16, u16) = (48, 20);
+
+    fn render(drawable: &dyn TerminalDrawble, size: (u16, u16), zoom: f32) -> HeadlessContext {
+        let mut ctx = HeadlessContext::new(size);
+        let camera = Camera::new(size, zoom, DEFAULT_CELL_ASPECT);
+        drawable.draw(&mut ctx);
+        ctx.draw_triangles(&camera);
+        ctx.draw_lines(&camera);
+        ctx.draw_points(&camera);
+        ctx.display(&mut io::sink()).unwrap();
+        ctx
+    }
+
+    //compares against snapshots/<name>.txt, UPDATE_SNAPSHOTS=1 writes the file instead
+    fn check_snapshot(name: &str, actual: String) {
+        let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "snapshots", name]
+            .iter()
+            .collect();
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, &actual).unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("missing snapshot {}, run with UPDATE_SNAPSHOTS=1", name));
+        assert_eq!(expected, actual, "snapshot {} changed", name);
+    }
+
+    fn check_drawable(name: &str, drawable: &dyn TerminalDrawble, size: (u16, u16), zoom: f32) {
+        let ctx = render(drawable, size, zoom);
+        let text = ctx.to_text();
+        assert!(
+            text.chars().any(|c| !c.is_whitespace()),
+            "{} drew nothing, is it inside the camera?",
+            name
+        );
+        check_snapshot(&format!("{}.txt", name), text);
+        check_snapshot(&format!("{}.styled.txt", name), ctx.to_styled());
+    }
+
+    #[test]
+    fn asteroid() {
+        let asteroid = Asteroid {
+            position: (0.0, 0.0),
+            speed: (0.0, 0.0),
+            angle: 0.3,
+            size: AsteroidSize::Huge,
+            angle_speed: 0.0,
+            color_palette: ColorPalette::Yellow,
+        };
+        check_drawable("asteroid", &asteroid, SIZE, 4.0);
+    }
+
+    #[test]
+    fn starship() {
+        let starship = StarShip {
+            position: (0.0, 0.0),
+            speed: (0.0, 0.0),
+            angle: 0.0,
+            size: StarShipSize::BigCluster,
+            angle_speed: 0.0,
+            disabled: false,
+        };
+        check_drawable("starship", &starship, SIZE, 4.0);
+    }
+
+    #[test]
+    fn ship() {
+        let camera = Camera::new(SIZE, 8.0, DEFAULT_CELL_ASPECT);
+        let mut ship = Ship::new();
+        //the spawn ring starts about 26 units wide, only a zoomed out camera sees it
+        check_drawable("ship_spawning", &ship, (100, 40), 1.0);
+
+        //spawned with the shield still up, then without it
+        ship.update(&camera, 2.5);
+        check_drawable("ship_shield", &ship, SIZE, 8.0);
+        ship.update(&camera, 1.0);
+        check_drawable("ship", &ship, SIZE, 8.0);
+    }
+
+    #[test]
+    fn logo() {
+        //the game reads logo.obj next to the binary, the test binary has none
+        let logo = parse_logo(include_str!("../asset/logo.obj").as_bytes());
+        check_drawable("logo", &DrawbleLogo::with_logo(logo), (100, 30), 2.0);
+    }
+}
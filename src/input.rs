@@ -0,0 +1,86 @@
+use crate::terminal::FOCUS_OUT;
+use std::io::Read;
+use termion::event::{Event, Key};
+use termion::input::Events;
+
+//how many times the same key counts in one frame, the rest are stale auto repeats
+pub const MAX_REPEATS: usize = 2;
+//keys that move or fire the ship, only these are capped
+const GAMEPLAY_KEYS: [Key; 4] = [Key::Left, Key::Right, Key::Up, Key::Char(' ')];
+
+//everything that arrived since the last frame
+pub struct Input {
+    pub keys: Vec<Key>,
+    pub focus_lost: bool,
+}
+
+impl Input {
+    //every key, for menus and typing
+    pub fn poll<R: Read>(stdin: &mut Events<R>) -> Input {
+        Input::read(stdin, &[])
+    }
+
+    //held movement keys queue up faster than the frames go, the extra repeats are dropped
+    pub fn poll_gameplay<R: Read>(stdin: &mut Events<R>) -> Input {
+        Input::read(stdin, &GAMEPLAY_KEYS)
+    }
+
+    fn read<R: Read>(stdin: &mut Events<R>, capped: &[Key]) -> Input {
+        let mut input = Input {
+            keys: Vec::new(),
+            focus_lost: false,
+        };
+
+        //the reader never blocks, it runs dry once the queue is empty
+        for result in stdin.by_ref() {
+            match result {
+                Ok(Event::Key(key))
+                    if !capped.contains(&key)
+                        || input.keys.iter().filter(|k| **k == key).count() < MAX_REPEATS =>
+                {
+                    input.keys.push(key)
+                }
+                Ok(Event::Unsupported(bytes)) if bytes == FOCUS_OUT => input.focus_lost = true,
+                _ => {}
+            }
+        }
+
+        input
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use termion::input::TermRead;
+
+    #[test]
+    fn focus_out_is_reported() {
+        let bytes: &[u8] = b"\x1b[Ia\x1b[O";
+        let input = Input::poll(&mut bytes.events());
+        assert!(input.focus_lost);
+        assert_eq!(input.keys, vec![Key::Char('a')]);
+
+        let bytes: &[u8] = b"\x1b[I";
+        assert!(!Input::poll(&mut bytes.events()).focus_lost);
+    }
+
+    #[test]
+    fn only_gameplay_repeats_are_capped() {
+        let bytes: &[u8] = b"aaa\x1b[D\x1b[D\x1b[D";
+        let input = Input::poll_gameplay(&mut bytes.events());
+        assert_eq!(
+            input.keys,
+            vec![
+                Key::Char('a'),
+                Key::Char('a'),
+                Key::Char('a'),
+                Key::Left,
+                Key::Left
+            ]
+        );
+
+        let bytes: &[u8] = b"aaa\x1b[D\x1b[D\x1b[D";
+        assert_eq!(Input::poll(&mut bytes.events()).keys.len(), 6);
+    }
+}
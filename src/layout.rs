@@ -0,0 +1,94 @@
+use crate::drawables::*;
+use crate::drawingcontext::DrawingContext;
+use crate::terminal::job_control;
+use std::io::{self, Stdout, Write};
+use termion::raw::RawTerminal;
+use termion::terminal_size;
+
+//smallest terminal every screen is laid out for, in columns and rows
+pub const MIN_SIZE: (u16, u16) = (80, 24);
+
+pub fn fits(size: (u16, u16)) -> bool {
+    size.0 >= MIN_SIZE.0 && size.1 >= MIN_SIZE.1
+}
+
+//shown instead of a screen until the terminal is big enough again
+pub fn too_small(
+    ctx: &mut dyn DrawingContext,
+    out: &mut dyn Write,
+    size: (u16, u16),
+) -> io::Result<()> {
+    let messages = [
+        String::from("Terminal too small"),
+        format!(
+            "enlarge your terminal to {}\u{d7}{}",
+            MIN_SIZE.0, MIN_SIZE.1
+        ),
+        format!("now {}\u{d7}{}", size.0, size.1),
+    ];
+    let message_y = (size.1 as f32 / 2.0 - messages.len() as f32 / 2.0)
+        .floor()
+        .max(0.0);
+
+    ctx.flush_text_entries();
+    ctx.flush_triangles();
+    ctx.flush_lines();
+    ctx.flush_points();
+    ctx.clear();
+
+    for (i, message) in messages.iter().enumerate() {
+        ctx.add_text_entry(&TextEntry {
+            position: (size.0 as f32 / 2.0, message_y + i as f32),
+            string: message.clone(),
+            color_palette: match i {
+                0 => TextColorPalette::Warning,
+                _ => TextColorPalette::Text,
+            },
+            align: TextAlign::Center,
+            ..TextEntry::empty_text_entry()
+        });
+    }
+
+    ctx.display(out)
+}
+
+//what happened to the terminal since the last frame
+pub struct TerminalState {
+    pub resized: bool,
+    //back from a suspend, everything has to be drawn again
+    pub resumed: bool,
+    //false while the too small notice is shown instead of the screen
+    pub fits: bool,
+}
+
+//checked at the start of every frame, by every screen
+//the context follows the new size and is redrawn in full after a suspend
+pub fn poll_terminal(
+    ctx: &mut dyn DrawingContext,
+    out: &mut RawTerminal<Stdout>,
+    size: &mut (u16, u16),
+) -> TerminalState {
+    let new_size = terminal_size().unwrap();
+    let resized = new_size != *size;
+    if resized {
+        *size = new_size;
+        ctx.resize(new_size);
+    }
+
+    let resumed = job_control(out);
+    if resumed {
+        ctx.invalidate();
+    }
+
+    //nothing is laid out for this size, wait for the window to grow
+    let fits = fits(*size);
+    if !fits {
+        too_small(ctx, out, *size).unwrap();
+    }
+
+    TerminalState {
+        resized,
+        resumed,
+        fits,
+    }
+}
@@ -1,55 +1,80 @@
 use crate::asciicontext::AsciiContext;
 use crate::drawables::*;
 use crate::drawingcontext::DrawingContext;
+use crate::input::Input;
+use crate::layout::*;
 use crate::plasma::*;
+use crate::settings::Settings;
+use crate::terminal::*;
 use curl::easy::*;
 use serde::Deserialize;
 use std::io::Write;
 use std::{thread, time};
 use termion::color;
 use termion::event::Key;
-use termion::input::Keys;
+use termion::input::Events;
 use termion::raw::RawTerminal;
 use termion::terminal_size;
 use termion::AsyncReader;
 
-pub fn leaderboard(stdin: &mut Keys<AsyncReader>, stdout: &mut RawTerminal<std::io::Stdout>) {
-    print!("{}{}", color::Bg(color::Black), color::Fg(color::White));
-    print!("{}", termion::cursor::Goto(1, 1));
-    print!("{}", termion::clear::All);
+//entries go on every other row starting here
+const LIST_Y: usize = 3;
+const MAX_PLAYERS_DISPLAYED: usize = 15;
 
-    let term_size = terminal_size().unwrap();
-    let mut scr: AsciiContext = AsciiContext::new(term_size);
+pub fn leaderboard(
+    stdin: &mut Events<AsyncReader>,
+    stdout: &mut RawTerminal<std::io::Stdout>,
+    settings: &Settings,
+) {
+    clear_screen(stdout);
+
+    let mut term_size = terminal_size().unwrap();
+    let mut scr: AsciiContext = AsciiContext::new(term_size, settings);
 
     let frame_fps = 20;
     let frame_len = time::Duration::from_micros(1000000 / frame_fps);
     let mut delta_time: f32 = frame_len.as_micros() as f32 / 1000000.0;
 
-    let mut plasma = FireDrawer::new(term_size);
-
     let players = get_leaderboard();
-    let players_displayed: usize = 15;
+    let mut players_displayed = players_fitting(term_size, players.list.len());
     let mut players_offset: i8 = 0;
 
-    loop {
+    let mut plasma = FireDrawer::new(term_size, list_bottom(players_displayed), settings);
+    //text and fire go out in one write, two flushes let the terminal show half a frame
+    let mut frame: Vec<u8> = Vec::new();
+
+    'screen: loop {
         let frame_start = time::Instant::now();
 
-        match stdin.next() {
-            Some(result) => match result {
-                Ok(key) => match key {
-                    Key::Ctrl('c') | Key::Esc | Key::Char('q') | Key::Char('\n') => {
-                        break;
-                    }
-                    Key::Up => players_offset = (players_offset - 1).max(0),
-                    Key::Down => {
-                        players_offset = (players_offset + 1)
-                            .min(players.list.len() as i8 - players_displayed as i8)
-                    }
-                    _ => {}
-                },
+        let input = Input::poll(stdin);
+        for key in input.keys {
+            match key {
+                Key::Ctrl('c') | Key::Esc | Key::Char('q') | Key::Char('\n') => {
+                    break 'screen;
+                }
+                Key::Up => players_offset = (players_offset - 1).max(0),
+                Key::Down => {
+                    players_offset =
+                        (players_offset + 1).min(players.list.len() as i8 - players_displayed as i8)
+                }
+                Key::Ctrl('z') => suspend(stdout),
                 _ => {}
-            },
-            _ => {}
+            }
+        }
+
+        let terminal = poll_terminal(&mut scr, stdout, &mut term_size);
+        if terminal.resized {
+            players_displayed = players_fitting(term_size, players.list.len());
+            players_offset = players_offset.min(players.list.len() as i8 - players_displayed as i8);
+            plasma.resize(term_size, list_bottom(players_displayed));
+        }
+        if !terminal.fits {
+            thread::sleep(frame_len);
+            continue;
+        }
+        //the text is drawn on top of the fire, so leftovers have to go first
+        if terminal.resized || terminal.resumed {
+            clear_screen(stdout);
         }
 
         plasma.update(delta_time);
@@ -57,42 +82,44 @@ pub fn leaderboard(stdin: &mut Keys<AsyncReader>, stdout: &mut RawTerminal<std::
         scr.flush_text_entries();
 
         for i in 0..players_displayed {
-            let message_y = 3;
+            let message_y = LIST_Y;
             let message = String::from("                                              ");
-            let message_x = (term_size.0 - message.len() as u16) / 2;
             scr.add_text_entry(&TextEntry {
-                position: (message_x as f32, message_y as f32 + i as f32 * 2.0),
+                position: (term_size.0 as f32 / 2.0, message_y as f32 + i as f32 * 2.0),
                 string: message,
                 color_palette: TextColorPalette::Text,
+                align: TextAlign::Center,
+                ..TextEntry::empty_text_entry()
             });
         }
 
         for i in 0..players_displayed {
             let pos = (i + players_offset as usize) as usize;
-            let message_y = 3;
+            let message_y = LIST_Y;
             let message = format!(
                 " {}. - {} : {} ",
                 pos + 1,
                 players.list[pos].name.clone(),
                 players.list[pos].score,
             );
-            let message_x = (term_size.0 - message.len() as u16) / 2;
             scr.add_text_entry(&TextEntry {
-                position: (message_x as f32, message_y as f32 + i as f32 * 2.0),
+                position: (term_size.0 as f32 / 2.0, message_y as f32 + i as f32 * 2.0),
                 string: message,
                 color_palette: match pos + 1 {
                     1..=3 => TextColorPalette::Warning,
                     4..=10 => TextColorPalette::Menu,
                     _ => TextColorPalette::Text,
                 },
+                align: TextAlign::Center,
+                ..TextEntry::empty_text_entry()
             });
         }
 
-        scr.display_text();
-
-        plasma.draw();
-
+        scr.display_text(&mut frame).unwrap();
+        plasma.draw(&mut frame).unwrap();
+        stdout.write_all(&frame).unwrap();
         stdout.flush().unwrap();
+        frame.clear();
 
         if let Some(i) = (frame_len).checked_sub(frame_start.elapsed()) {
             thread::sleep(i)
@@ -104,54 +131,66 @@ pub fn leaderboard(stdin: &mut Keys<AsyncReader>, stdout: &mut RawTerminal<std::
 }
 
 pub fn leaderboard_entry(
-    stdin: &mut Keys<AsyncReader>,
+    stdin: &mut Events<AsyncReader>,
     stdout: &mut RawTerminal<std::io::Stdout>,
+    settings: &Settings,
     score: u32,
 ) {
-    print!("{}{}", color::Bg(color::Black), color::Fg(color::White));
-    print!("{}", termion::cursor::Goto(1, 1));
-    print!("{}", termion::clear::All);
+    clear_screen(stdout);
 
-    let term_size = terminal_size().unwrap();
-    let mut scr: AsciiContext = AsciiContext::new(term_size);
+    let mut term_size = terminal_size().unwrap();
+    let mut scr: AsciiContext = AsciiContext::new(term_size, settings);
 
     let frame_fps = 20;
     let frame_len = time::Duration::from_micros(1000000 / frame_fps);
     let mut delta_time: f32 = frame_len.as_micros() as f32 / 1000000.0;
 
-    let mut plasma = FireDrawer::new(term_size);
-
     let messages_height = 3;
 
+    let mut plasma = FireDrawer::new(term_size, list_bottom(messages_height), settings);
+    //text and fire go out in one write, two flushes let the terminal show half a frame
+    let mut frame: Vec<u8> = Vec::new();
+
     let mut input_name = String::from("");
     let mut submit = false;
 
-    loop {
+    'screen: loop {
         let frame_start = time::Instant::now();
 
-        match stdin.next() {
-            Some(result) => match result {
-                Ok(key) => match key {
-                    Key::Ctrl('c') | Key::Esc => {
-                        break;
+        let input = Input::poll(stdin);
+        for key in input.keys {
+            match key {
+                Key::Ctrl('c') | Key::Esc => {
+                    break 'screen;
+                }
+                Key::Backspace => {
+                    input_name.pop();
+                }
+                Key::Char('\n') => {
+                    submit = true;
+                    break 'screen;
+                }
+                Key::Char(c) => {
+                    if c.is_alphanumeric() && input_name.chars().count() < 9 {
+                        input_name.push(c);
                     }
-                    Key::Backspace => {
-                        input_name.pop();
-                    }
-                    Key::Char('\n') => {
-                        submit = true;
-                        break;
-                    }
-                    Key::Char(c) => {
-                        if c.is_alphanumeric() && input_name.len() < 9 {
-                            input_name.push(c);
-                        }
-                    }
-                    _ => {}
-                },
+                }
+                Key::Ctrl('z') => suspend(stdout),
                 _ => {}
-            },
-            _ => {}
+            }
+        }
+
+        let terminal = poll_terminal(&mut scr, stdout, &mut term_size);
+        if terminal.resized {
+            plasma.resize(term_size, list_bottom(messages_height));
+        }
+        if !terminal.fits {
+            thread::sleep(frame_len);
+            continue;
+        }
+        //the text is drawn on top of the fire, so leftovers have to go first
+        if terminal.resized || terminal.resumed {
+            clear_screen(stdout);
         }
 
         plasma.update(delta_time);
@@ -159,18 +198,19 @@ pub fn leaderboard_entry(
         scr.flush_text_entries();
 
         for i in 0..messages_height {
-            let message_y = 3;
+            let message_y = LIST_Y;
             let message = String::from("                                                    ");
-            let message_x = (term_size.0 - message.len() as u16) / 2;
             scr.add_text_entry(&TextEntry {
-                position: (message_x as f32, message_y as f32 + i as f32 * 2.0),
+                position: (term_size.0 as f32 / 2.0, message_y as f32 + i as f32 * 2.0),
                 string: message,
                 color_palette: TextColorPalette::Text,
+                align: TextAlign::Center,
+                ..TextEntry::empty_text_entry()
             });
         }
 
         for i in 0..messages_height {
-            let message_y = 3;
+            let message_y = LIST_Y;
             let message = match i {
                 0 => format!("Your score was : {}", score),
                 1 => String::from("Enter your name: (ESC to cancel)"),
@@ -179,23 +219,24 @@ pub fn leaderboard_entry(
                     false => String::from(&input_name),
                 },
             };
-            let message_x = (term_size.0 - message.len() as u16) / 2;
             scr.add_text_entry(&TextEntry {
-                position: (message_x as f32, message_y as f32 + i as f32 * 2.0),
+                position: (term_size.0 as f32 / 2.0, message_y as f32 + i as f32 * 2.0),
                 string: message,
                 color_palette: match i {
                     0 => TextColorPalette::Warning,
                     1 => TextColorPalette::Text,
                     _ => TextColorPalette::Menu,
                 },
+                align: TextAlign::Center,
+                ..TextEntry::empty_text_entry()
             });
         }
 
-        scr.display_text();
-
-        plasma.draw();
-
+        scr.display_text(&mut frame).unwrap();
+        plasma.draw(&mut frame).unwrap();
+        stdout.write_all(&frame).unwrap();
         stdout.flush().unwrap();
+        frame.clear();
 
         if let Some(i) = (frame_len).checked_sub(frame_start.elapsed()) {
             thread::sleep(i)
@@ -210,6 +251,29 @@ pub fn leaderboard_entry(
     }
 }
 
+//short terminals show fewer entries, the rest is reached by scrolling
+fn players_fitting(term_size: (u16, u16), count: usize) -> usize {
+    let rows = (term_size.1 as usize).saturating_sub(LIST_Y);
+    rows.div_ceil(2).min(MAX_PLAYERS_DISPLAYED).min(count)
+}
+
+//first row below a list of entries, free for the fire
+fn list_bottom(entries: usize) -> u16 {
+    (LIST_Y + entries * 2).saturating_sub(1) as u16
+}
+
+fn clear_screen(stdout: &mut RawTerminal<std::io::Stdout>) {
+    write!(
+        stdout,
+        "{}{}{}{}",
+        color::Bg(color::Black),
+        color::Fg(color::White),
+        termion::cursor::Goto(1, 1),
+        termion::clear::All
+    )
+    .unwrap();
+}
+
 #[derive(Debug, Deserialize)]
 struct LeaderboardEntry {
     name: String,
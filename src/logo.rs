@@ -1,4 +1,3 @@
-use crate::asciicontext::AsciiContext;
 use crate::drawables::*;
 use crate::drawingcontext::DrawingContext;
 use crate::sprite::*;
@@ -9,9 +8,6 @@ use std::io::{self, BufRead};
 use std::path::PathBuf;
 
 pub fn load_logo() -> Vec<Triangle> {
-    let mut vertices: Vec<Vec2> = Vec::new();
-    let mut triangles: Vec<Triangle> = Vec::new();
-
     if let Some(binary_dir) = current_binary_directory() {
         let file = match File::open(binary_dir.join("logo.obj")) {
             Ok(file) => file,
@@ -21,39 +17,47 @@ pub fn load_logo() -> Vec<Triangle> {
             }
         };
 
-        let reader = io::BufReader::new(file);
-        for line in reader.lines() {
-            let line = match line {
-                Ok(line) => line,
-                Err(e) => {
-                    eprintln!("Error reading line: {}", e);
-                    continue;
-                }
-            };
+        return parse_logo(io::BufReader::new(file));
+    }
 
-            match line.chars().next() {
-                Some(c) => match c {
-                    'v' => {
-                        let parts: Vec<&str> = line.split_whitespace().collect();
-                        let x = parts[1].parse::<f32>().unwrap();
-                        let y = parts[3].parse::<f32>().unwrap();
-                        vertices.push((x, y));
-                    }
-                    'f' => {
-                        let parts: Vec<&str> = line.split_whitespace().collect();
-                        let a = (parts[1].parse::<i16>().unwrap() - 1) as usize;
-                        let b = (parts[2].parse::<i16>().unwrap() - 1) as usize;
-                        let c = (parts[3].parse::<i16>().unwrap() - 1) as usize;
-                        triangles.push(Triangle {
-                            points: [vertices[a], vertices[b], vertices[c]],
-                            colors: [0.0, 0.0, 0.0],
-                            color_palette: ColorPalette::Custom,
-                        });
-                    }
-                    _ => {}
-                },
-                None => {}
+    [].to_vec()
+}
+
+pub fn parse_logo(reader: impl BufRead) -> Vec<Triangle> {
+    let mut vertices: Vec<Vec2> = Vec::new();
+    let mut triangles: Vec<Triangle> = Vec::new();
+
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("Error reading line: {}", e);
+                continue;
             }
+        };
+
+        match line.chars().next() {
+            Some(c) => match c {
+                'v' => {
+                    let parts: Vec<&str> = line.split_whitespace().collect();
+                    let x = parts[1].parse::<f32>().unwrap();
+                    let y = parts[3].parse::<f32>().unwrap();
+                    vertices.push((x, y));
+                }
+                'f' => {
+                    let parts: Vec<&str> = line.split_whitespace().collect();
+                    let a = (parts[1].parse::<i16>().unwrap() - 1) as usize;
+                    let b = (parts[2].parse::<i16>().unwrap() - 1) as usize;
+                    let c = (parts[3].parse::<i16>().unwrap() - 1) as usize;
+                    triangles.push(Triangle {
+                        points: [vertices[a], vertices[b], vertices[c]],
+                        colors: [0.0, 0.0, 0.0],
+                        color_palettes: [ColorPalette::Gray; 3],
+                    });
+                }
+                _ => {}
+            },
+            None => {}
         }
     }
 
@@ -79,9 +83,13 @@ pub struct DrawbleLogo {
 
 impl DrawbleLogo {
     pub fn new() -> DrawbleLogo {
+        DrawbleLogo::with_logo(load_logo())
+    }
+
+    pub fn with_logo(logo: Vec<Triangle>) -> DrawbleLogo {
         DrawbleLogo {
             color_palette: ColorPalette::Green,
-            logo: load_logo(),
+            logo,
             time: 0.0,
         }
     }
@@ -98,7 +106,7 @@ impl Sprite for DrawbleLogo {
 }
 
 impl TerminalDrawble for DrawbleLogo {
-    fn draw(&self, ctx: &mut AsciiContext) {
+    fn draw(&self, ctx: &mut dyn DrawingContext) {
         let shader_scale = (25.0 + (0.3 * self.time).cos() * 5.0, 25.0);
         let shader_offset = (
             0.0 + (0.4 * self.time).cos(),
@@ -106,7 +114,7 @@ impl TerminalDrawble for DrawbleLogo {
         );
         let mut logo_shaded = vec![EMPTY_TRIANGLE; self.logo.len()];
         self.logo.iter().enumerate().for_each(|(i, triangle)| {
-            logo_shaded[i].color_palette = self.color_palette;
+            logo_shaded[i].color_palettes = [self.color_palette; 3];
             for j in 0..3 {
                 let point = (
                     triangle.points[j].0 * shader_scale.0 + shader_offset.0,
@@ -117,6 +125,6 @@ impl TerminalDrawble for DrawbleLogo {
                     (point.0 * 0.1 + point.1 + 0.1 + self.time * 0.5).cos() * 0.4 + 0.6;
             }
         });
-        ctx.add_triangles(&logo_shaded);
+        ctx.add_shape(&logo_shaded);
     }
 }
@@ -5,6 +5,9 @@ pub mod drawables;
 pub mod drawingcontext;
 pub mod enemy;
 pub mod game;
+pub mod headlesscontext;
+pub mod input;
+pub mod layout;
 pub mod leaderboard;
 pub mod logo;
 pub mod menu;
@@ -13,13 +16,34 @@ pub mod menu_objects;
 pub mod particle;
 pub mod plasma;
 pub mod powerup;
+pub mod settings;
 pub mod ship;
 pub mod sprite;
 pub mod starship;
+pub mod terminal;
 pub mod terminaldrawable;
+pub mod theme;
 
 use crate::menu::*;
+use crate::settings::Settings;
+use std::panic::{self, AssertUnwindSafe};
 
 fn main() {
-    menu();
+    let args: Vec<String> = std::env::args().collect();
+    //bad arguments are reported before the terminal is touched
+    let settings = match Settings::from_args(&args) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, settings::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    //unwinding drops the terminal guards, the message is printed after them
+    terminal::install_panic_hook();
+    terminal::install_signal_handlers();
+    if panic::catch_unwind(AssertUnwindSafe(|| menu(&settings))).is_err() {
+        terminal::print_panic_message();
+        std::process::exit(101);
+    }
 }
@@ -2,11 +2,15 @@ use crate::asciicontext::AsciiContext;
 use crate::drawables::*;
 use crate::drawingcontext::DrawingContext;
 use crate::game::*;
+use crate::input::Input;
+use crate::layout::*;
 use crate::leaderboard::*;
 use crate::logo::*;
 use crate::menu_help::*;
 use crate::menu_objects::*;
+use crate::settings::Settings;
 use crate::sprite::Sprite;
+use crate::terminal::*;
 use crate::terminaldrawable::TerminalDrawble;
 use std::io::{stdout, Write};
 use std::{thread, time};
@@ -17,62 +21,68 @@ use termion::raw::IntoRawMode;
 use termion::raw::RawTerminal;
 use termion::terminal_size;
 
-pub fn menu() {
-    let term_size = terminal_size().unwrap();
-    let mut scr: AsciiContext = AsciiContext::new(term_size);
-    let mut stdin = async_stdin().keys();
+pub fn menu(settings: &Settings) {
+    let mut term_size = terminal_size().unwrap();
+    let mut scr: AsciiContext = AsciiContext::new(term_size, settings);
+    let mut stdin = async_stdin().events();
     let mut stdout: RawTerminal<std::io::Stdout> = stdout().into_raw_mode().unwrap();
+    let _guard = TerminalGuard::enter();
 
     let frame_fps = 24;
     let frame_len = time::Duration::from_micros(1000000 / frame_fps);
     let mut delta_time: f32 = frame_len.as_micros() as f32 / 1000000.0;
 
-    let camera = Camera {
-        position: (0.0, 0.0),
-        size: (term_size.0 as f32, term_size.1 as f32),
-        zoom: 2.0,
-    };
+    let mut camera = Camera::new(term_size, 2.0, settings.cell_aspect);
 
     let mut logo: DrawbleLogo = DrawbleLogo::new();
 
     let messages = ["New game", "Help", "Objects", "Leader board", "Exit"];
     let mut message_selection: i8 = 0;
 
-    loop {
+    'screen: loop {
         let frame_start = time::Instant::now();
 
-        match stdin.next() {
-            Some(result) => match result {
-                Ok(key) => match key {
-                    Key::Ctrl('c') => {
-                        break;
-                    }
-                    Key::Char('\n') => {
-                        match message_selection {
-                            0 => game(&mut stdin, &mut stdout),
-                            1 => menu_help(&mut stdin, &mut stdout),
-                            2 => menu_objects(&mut stdin, &mut stdout),
-                            3 => leaderboard(&mut stdin, &mut stdout),
-                            _ => break,
-                        };
-                    }
-                    Key::Up => message_selection = (message_selection - 1).max(0),
-                    Key::Down => {
-                        message_selection = (message_selection + 1).min((messages.len() - 1) as i8)
-                    }
-                    _ => {}
-                },
+        let input = Input::poll(&mut stdin);
+        for key in input.keys {
+            match key {
+                Key::Ctrl('c') => {
+                    break 'screen;
+                }
+                Key::Char('\n') => {
+                    match message_selection {
+                        0 => game(&mut stdin, &mut stdout, settings),
+                        1 => menu_help(&mut stdin, &mut stdout, settings),
+                        2 => menu_objects(&mut stdin, &mut stdout, settings),
+                        3 => leaderboard(&mut stdin, &mut stdout, settings),
+                        _ => break 'screen,
+                    };
+                    scr.invalidate();
+                    //whatever was queued behind the enter belonged to the screen that just closed
+                    break;
+                }
+                Key::Up => message_selection = (message_selection - 1).max(0),
+                Key::Down => {
+                    message_selection = (message_selection + 1).min((messages.len() - 1) as i8)
+                }
+                Key::Ctrl('z') => suspend(&mut stdout),
                 _ => {}
-            },
-            _ => {}
+            }
         }
 
-        logo.update(&camera, delta_time);
+        let terminal = poll_terminal(&mut scr, &mut stdout, &mut term_size);
+        if terminal.resized {
+            camera.resize(term_size);
+        }
+        if !terminal.fits {
+            thread::sleep(frame_len);
+            continue;
+        }
 
-        print!("{}", termion::cursor::Goto(1, 1));
+        logo.update(&camera, delta_time);
 
         scr.flush_text_entries();
         scr.flush_triangles();
+        scr.flush_lines();
         scr.flush_points();
         scr.clear();
 
@@ -80,42 +90,51 @@ pub fn menu() {
             position: (0.0, 4.0),
             string: String::from("Copyright 2023 - c64cosmin - Cosmin MUNTEANU"),
             color_palette: TextColorPalette::Text,
+            ..TextEntry::empty_text_entry()
         });
         scr.add_text_entry(&TextEntry {
             position: (0.0, 0.0),
             string: String::from("Follow me on Twitter @c64cosmin  https://twitter.com/c64cosmin"),
             color_palette: TextColorPalette::Text,
+            ..TextEntry::empty_text_entry()
         });
         scr.add_text_entry(&TextEntry {
             position: (0.0, 2.0),
             string: String::from("Or visit my webpage for more games  https://www.stupidrat.com"),
             color_palette: TextColorPalette::Text,
+            ..TextEntry::empty_text_entry()
         });
 
         for i in 0..messages.len() {
-            let message_y = (term_size.1 - messages.len() as u16 * 3) * 2 / 3;
+            let message_y = term_size.1.saturating_sub(messages.len() as u16 * 3) * 2 / 3;
             let mut message = String::from(messages[i]);
             if message_selection == i as i8 {
                 message = format!(">>> {} <<<", messages[i]);
             }
-            let message_x = (term_size.0 - message.len() as u16) / 2;
             scr.add_text_entry(&TextEntry {
-                position: (message_x as f32, message_y as f32 + i as f32 * 3.0),
+                position: (term_size.0 as f32 / 2.0, message_y as f32 + i as f32 * 3.0),
                 string: message,
                 color_palette: match message_selection == i as i8 {
                     true => TextColorPalette::Warning,
                     false => TextColorPalette::Menu,
                 },
+                align: TextAlign::Center,
+                style: TextStyle {
+                    bold: message_selection == i as i8,
+                    underline: false,
+                },
+                ..TextEntry::empty_text_entry()
             });
         }
 
         logo.draw(&mut scr);
 
         scr.draw_triangles(&camera);
+        scr.draw_lines(&camera);
         scr.draw_points(&camera);
-        scr.display();
-
-        stdout.flush().unwrap();
+        scr.persist(delta_time);
+        scr.effects(&camera);
+        scr.display(&mut stdout).unwrap();
 
         if let Some(i) = (frame_len).checked_sub(frame_start.elapsed()) {
             thread::sleep(i)
@@ -125,7 +144,13 @@ pub fn menu() {
             time::Instant::now().duration_since(frame_start).as_micros() as f32 / 1000000.0;
     }
 
-    print!("{}", termion::cursor::Goto(1, 1));
-    print!("{}", termion::clear::All);
+    write!(
+        stdout,
+        "{}{}",
+        termion::cursor::Goto(1, 1),
+        termion::clear::All
+    )
+    .unwrap();
+    stdout.flush().unwrap();
     stdout.suspend_raw_mode().unwrap();
 }
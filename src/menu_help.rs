@@ -1,30 +1,32 @@
 use crate::asciicontext::AsciiContext;
 use crate::drawables::*;
 use crate::drawingcontext::DrawingContext;
+use crate::input::Input;
+use crate::layout::*;
 use crate::plasma::*;
+use crate::settings::Settings;
+use crate::terminal::*;
 use std::io::Write;
 use std::{thread, time};
 use termion::event::Key;
-use termion::input::Keys;
+use termion::input::Events;
 use termion::raw::RawTerminal;
 use termion::terminal_size;
 use termion::AsyncReader;
 
-pub fn menu_help(stdin: &mut Keys<AsyncReader>, stdout: &mut RawTerminal<std::io::Stdout>) {
-    let term_size = terminal_size().unwrap();
-    let mut scr: AsciiContext = AsciiContext::new(term_size);
+pub fn menu_help(
+    stdin: &mut Events<AsyncReader>,
+    stdout: &mut RawTerminal<std::io::Stdout>,
+    settings: &Settings,
+) {
+    let mut term_size = terminal_size().unwrap();
+    let mut scr: AsciiContext = AsciiContext::new(term_size, settings);
 
     let frame_fps = 20;
     let frame_len = time::Duration::from_micros(1000000 / frame_fps);
     let mut delta_time: f32 = frame_len.as_micros() as f32 / 1000000.0;
 
-    let camera = Camera {
-        position: (0.0, 0.0),
-        size: (term_size.0 as f32, term_size.1 as f32),
-        zoom: 2.0,
-    };
-
-    let mut plasma = FireDrawer::new(term_size);
+    let mut camera = Camera::new(term_size, 2.0, settings.cell_aspect);
 
     let messages = [
         "Recommended font size : 10",
@@ -53,52 +55,97 @@ pub fn menu_help(stdin: &mut Keys<AsyncReader>, stdout: &mut RawTerminal<std::io
         "Up            : Move forward",
         "Space         : Fire        ",
         "P             : Pause       ",
+        "Ctrl-Z        : Suspend     ",
+        "F3            : Frame stats ",
         "Q or Ctrl-C   : Exit        ",
+        "",
+        "The game pauses when the terminal loses focus or comes back from Ctrl-Z",
     ];
 
-    print!("{}", termion::cursor::Goto(1, 1));
-
-    scr.flush_text_entries();
-    scr.flush_triangles();
-    scr.flush_points();
-    scr.clear();
-
-    for i in 0..messages.len() {
-        let message_y = 6;
-        let message = String::from(messages[i]);
-        let message_x = (term_size.0 - message.len() as u16) / 2;
-        scr.add_text_entry(&TextEntry {
-            position: (message_x as f32, message_y as f32 + i as f32),
-            string: message,
-            color_palette: TextColorPalette::Text,
-        });
-    }
+    let (mut message_y, mut messages_shown) = help_layout(term_size, messages.len());
+    let mut plasma = FireDrawer::new(term_size, (message_y + messages_shown) as u16, settings);
+    //text and fire go out in one write, two flushes let the terminal show half a frame
+    let mut frame: Vec<u8> = Vec::new();
+    //first line shown when the text is taller than the screen
+    let mut scroll: usize = 0;
 
-    scr.draw_triangles(&camera);
-    scr.draw_points(&camera);
-    scr.display();
+    //the text only changes when the window is resized or scrolled
+    let mut redraw = true;
 
-    loop {
+    'screen: loop {
         let frame_start = time::Instant::now();
 
-        match stdin.next() {
-            Some(result) => match result {
-                Ok(key) => match key {
-                    Key::Ctrl('c') | Key::Esc | Key::Char('q') | Key::Char('\n') => {
-                        break;
-                    }
-                    _ => {}
-                },
+        let input = Input::poll(stdin);
+        for key in input.keys {
+            match key {
+                Key::Ctrl('c') | Key::Esc | Key::Char('q') | Key::Char('\n') => {
+                    break 'screen;
+                }
+                Key::Up => {
+                    scroll = scroll.saturating_sub(1);
+                    redraw = true;
+                }
+                Key::Down => {
+                    scroll = (scroll + 1).min(messages.len() - messages_shown);
+                    redraw = true;
+                }
+                Key::Ctrl('z') => suspend(stdout),
                 _ => {}
-            },
-            _ => {}
+            }
         }
 
-        plasma.update(delta_time);
+        let terminal = poll_terminal(&mut scr, stdout, &mut term_size);
+        if terminal.resized {
+            camera.resize(term_size);
+            (message_y, messages_shown) = help_layout(term_size, messages.len());
+            scroll = scroll.min(messages.len() - messages_shown);
+            plasma.resize(term_size, (message_y + messages_shown) as u16);
+            redraw = true;
+        }
+        if terminal.resumed {
+            redraw = true;
+        }
+        if !terminal.fits {
+            thread::sleep(frame_len);
+            continue;
+        }
+
+        if redraw {
+            scr.flush_text_entries();
+            scr.flush_triangles();
+            scr.flush_lines();
+            scr.flush_points();
+            scr.clear();
+
+            for (i, message) in messages
+                .iter()
+                .skip(scroll)
+                .take(messages_shown)
+                .enumerate()
+            {
+                scr.add_text_entry(&TextEntry {
+                    position: (term_size.0 as f32 / 2.0, message_y as f32 + i as f32),
+                    string: String::from(*message),
+                    color_palette: TextColorPalette::Text,
+                    align: TextAlign::Center,
+                    ..TextEntry::empty_text_entry()
+                });
+            }
+
+            scr.draw_triangles(&camera);
+            scr.draw_lines(&camera);
+            scr.draw_points(&camera);
+            scr.display(&mut frame).unwrap();
+
+            redraw = false;
+        }
 
-        plasma.draw();
+        plasma.update(delta_time);
 
+        plasma.draw(&mut frame).unwrap();
+        stdout.write_all(&frame).unwrap();
         stdout.flush().unwrap();
+        frame.clear();
 
         if let Some(i) = (frame_len).checked_sub(frame_start.elapsed()) {
             thread::sleep(i)
@@ -108,3 +155,11 @@ pub fn menu_help(stdin: &mut Keys<AsyncReader>, stdout: &mut RawTerminal<std::io
             time::Instant::now().duration_since(frame_start).as_micros() as f32 / 1000000.0;
     }
 }
+
+//row of the first line and how many lines fit below it
+//short terminals lose the top margin first, then scroll the text
+fn help_layout(term_size: (u16, u16), lines: usize) -> (usize, usize) {
+    let rows = term_size.1 as usize;
+    let message_y = rows.saturating_sub(lines).min(6);
+    (message_y, (rows - message_y).min(lines))
+}
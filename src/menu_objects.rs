@@ -1,35 +1,38 @@
-use crate::asciicontext::AsciiContext;
+use crate::asciicontext::{vertex_shader, AsciiContext};
 use crate::asteroid::*;
 use crate::drawables::*;
 use crate::drawingcontext::DrawingContext;
 use crate::enemy::*;
+use crate::input::Input;
+use crate::layout::*;
 use crate::powerup::PowerupSize;
 use crate::powerup::*;
+use crate::settings::Settings;
 use crate::sprite::Sprite;
 use crate::starship::StarShipSize;
 use crate::starship::*;
+use crate::terminal::*;
 use crate::terminaldrawable::TerminalDrawble;
-use std::io::Write;
 use std::{thread, time};
 use termion::event::Key;
-use termion::input::Keys;
+use termion::input::Events;
 use termion::raw::RawTerminal;
 use termion::terminal_size;
 use termion::AsyncReader;
 
-pub fn menu_objects(stdin: &mut Keys<AsyncReader>, stdout: &mut RawTerminal<std::io::Stdout>) {
-    let term_size = terminal_size().unwrap();
-    let mut scr: AsciiContext = AsciiContext::new(term_size);
+pub fn menu_objects(
+    stdin: &mut Events<AsyncReader>,
+    stdout: &mut RawTerminal<std::io::Stdout>,
+    settings: &Settings,
+) {
+    let mut term_size = terminal_size().unwrap();
+    let mut scr: AsciiContext = AsciiContext::new(term_size, settings);
 
     let frame_fps = 24;
     let frame_len = time::Duration::from_micros(1000000 / frame_fps);
     let mut delta_time: f32 = frame_len.as_micros() as f32 / 1000000.0;
 
-    let camera = Camera {
-        position: (0.0, 0.0),
-        size: (term_size.0 as f32, term_size.1 as f32),
-        zoom: 2.0,
-    };
+    let mut camera = Camera::new(term_size, 2.0, settings.cell_aspect);
 
     let stationary = (0.0, 0.0);
     let mut objects: Enemies = Enemies::new();
@@ -130,74 +133,77 @@ pub fn menu_objects(stdin: &mut Keys<AsyncReader>, stdout: &mut RawTerminal<std:
         size: PowerupSize::Shield,
     }));
 
-    loop {
+    'screen: loop {
         let frame_start = time::Instant::now();
 
-        match stdin.next() {
-            Some(result) => match result {
-                Ok(key) => match key {
-                    Key::Ctrl('c') | Key::Esc | Key::Char('q') | Key::Char('\n') => {
-                        break;
-                    }
-                    _ => {}
-                },
+        let input = Input::poll(stdin);
+        for key in input.keys {
+            match key {
+                Key::Ctrl('c') | Key::Esc | Key::Char('q') | Key::Char('\n') => {
+                    break 'screen;
+                }
+                Key::Ctrl('z') => suspend(stdout),
                 _ => {}
-            },
-            _ => {}
+            }
         }
 
-        objects.update(&camera, delta_time);
+        let terminal = poll_terminal(&mut scr, stdout, &mut term_size);
+        if terminal.resized {
+            camera.resize(term_size);
+        }
+        if !terminal.fits {
+            thread::sleep(frame_len);
+            continue;
+        }
 
-        print!("{}", termion::cursor::Goto(1, 1));
+        objects.update(&camera, delta_time);
 
         scr.flush_text_entries();
         scr.flush_triangles();
+        scr.flush_lines();
         scr.flush_points();
         scr.clear();
 
-        scr.add_text_entry(&TextEntry {
-            position: (10.0, 10.0),
-            string: String::from("Asteroids"),
-            color_palette: TextColorPalette::Text,
-        });
-        scr.add_text_entry(&TextEntry {
-            position: (10.0, 31.0),
-            string: String::from("Star-ships"),
-            color_palette: TextColorPalette::Text,
-        });
-        scr.add_text_entry(&TextEntry {
-            position: (10.0, 48.0),
-            string: String::from("Powerups"),
-            color_palette: TextColorPalette::Text,
-        });
-        scr.add_text_entry(&TextEntry {
-            position: (54.0, 56.0),
-            string: String::from("Rapid Fire"),
-            color_palette: TextColorPalette::Text,
-        });
-        scr.add_text_entry(&TextEntry {
-            position: (94.0, 56.0),
-            string: String::from("Split Fire"),
-            color_palette: TextColorPalette::Text,
-        });
-        scr.add_text_entry(&TextEntry {
-            position: (132.0, 56.0),
-            string: String::from("Piercing Bullets"),
-            color_palette: TextColorPalette::Text,
-        });
-        scr.add_text_entry(&TextEntry {
-            position: (177.0, 56.0),
-            string: String::from("Shield"),
-            color_palette: TextColorPalette::Text,
-        });
+        //labels follow the objects, wherever the camera puts them on this screen
+        let rows = [
+            ("Asteroids", -10.0),
+            ("Star-ships", 1.0),
+            ("Powerups", 10.0),
+        ];
+        for (name, y) in rows {
+            let position = vertex_shader(&(0.0, y), &camera);
+            scr.add_text_entry(&TextEntry {
+                position: (2.0, position.1.floor()),
+                string: String::from(name),
+                color_palette: TextColorPalette::Text,
+                ..TextEntry::empty_text_entry()
+            });
+        }
+        let powerups = [
+            ("Rapid Fire", -15.0),
+            ("Split Fire", -5.0),
+            ("Piercing Bullets", 5.0),
+            ("Shield", 15.0),
+        ];
+        for (name, x) in powerups {
+            let position = vertex_shader(&(x, 14.0), &camera);
+            scr.add_text_entry(&TextEntry {
+                position: (position.0, position.1.floor()),
+                string: String::from(name),
+                color_palette: TextColorPalette::Text,
+                align: TextAlign::Center,
+                ..TextEntry::empty_text_entry()
+            });
+        }
 
         objects.draw(&mut scr);
 
         scr.draw_triangles(&camera);
+        scr.draw_lines(&camera);
         scr.draw_points(&camera);
-        scr.display();
-
-        stdout.flush().unwrap();
+        scr.persist(delta_time);
+        scr.effects(&camera);
+        scr.display(stdout).unwrap();
 
         if let Some(i) = (frame_len).checked_sub(frame_start.elapsed()) {
             thread::sleep(i)
@@ -1,4 +1,3 @@
-use crate::asciicontext::AsciiContext;
 use crate::drawables::*;
 use crate::drawingcontext::DrawingContext;
 use crate::sprite::*;
@@ -15,7 +14,7 @@ pub struct Particle {
 }
 
 impl TerminalDrawble for Particle {
-    fn draw(&self, ctx: &mut AsciiContext) {
+    fn draw(&self, ctx: &mut dyn DrawingContext) {
         let point = Point {
             position: self.position,
             color: self.life / self.life_max / 2.0,
@@ -48,6 +47,10 @@ impl Sprite for Particle {
         self.life -= delta;
     }
 
+    fn clamp(&mut self, bounds: Vec2) {
+        self.position = clamp_position(self.position, bounds);
+    }
+
     fn is_alive(&self) -> bool {
         return self.life > 0.0;
     }
@@ -1,5 +1,12 @@
+use crate::asciicontext::{write_bg, write_fg};
 use crate::drawables::*;
+use crate::settings::Settings;
+use crate::theme::Theme;
 use rand::Rng;
+use std::io::{self, Write};
+
+//rows taken by the fire at the bottom of the screen, fewer when the text above needs them
+const FIRE_HEIGHT: usize = 27;
 
 pub struct FireDrawer {
     bitmap: Vec<f32>,
@@ -7,21 +14,40 @@ pub struct FireDrawer {
     terminal_size: (u16, u16),
     wind: i16,
     time: f32,
+    buffer: Vec<u8>,
+    theme: Theme,
+    color_mode: ColorMode,
+    dither: bool,
 }
 
 impl FireDrawer {
-    pub fn new(size: (u16, u16)) -> FireDrawer {
-        let plasma_size = (size.0 as usize, 27);
+    //top is the first row the fire may use, the rows above it are left to the text
+    pub fn new(size: (u16, u16), top: u16, settings: &Settings) -> FireDrawer {
+        let plasma_size = (size.0 as usize, fire_height(size, top));
         FireDrawer {
             bitmap: vec![0.0; plasma_size.0 * plasma_size.1],
             size: plasma_size,
             terminal_size: size,
             wind: 0,
             time: 10.0,
+            buffer: Vec::with_capacity(plasma_size.0 * plasma_size.1 * 24),
+            theme: settings.theme.clone(),
+            color_mode: settings.color_mode,
+            dither: settings.dither,
         }
     }
 
+    pub fn resize(&mut self, size: (u16, u16), top: u16) {
+        self.size = (size.0 as usize, fire_height(size, top));
+        self.terminal_size = size;
+        self.bitmap = vec![0.0; self.size.0 * self.size.1];
+    }
+
     pub fn update(&mut self, delta: f32) {
+        //no room left below the text
+        if self.size.1 == 0 {
+            return;
+        }
         let mut rnd = rand::thread_rng();
 
         self.time -= delta;
@@ -55,30 +81,62 @@ impl FireDrawer {
         self.bitmap[x + y * self.size.0 as usize] = value;
     }
 
-    pub fn draw(&self) {
-        print!(
+    pub fn draw(&mut self, out: &mut dyn Write) -> io::Result<()> {
+        if self.size.1 == 0 {
+            return Ok(());
+        }
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+
+        write!(
+            buffer,
             "{}",
             termion::cursor::Goto(1, self.terminal_size.1 - self.size.1 as u16 + 1)
-        );
-        self.bitmap
-            .chunks(self.size.0 as usize)
-            .for_each(|line| line.iter().for_each(|&pixel| self.fill_color(pixel)));
+        )?;
+        for (i, &pixel) in self.bitmap.iter().enumerate() {
+            let dither = self.dither_offset(i % self.size.0, i / self.size.0);
+            self.fill_color(&mut buffer, pixel, dither)?;
+        }
+
+        out.write_all(&buffer)?;
+        self.buffer = buffer;
+        out.flush()
+    }
+
+    //ordered dither offset of one step between two glyphs of the fire
+    fn dither_offset(&self, x: usize, y: usize) -> f32 {
+        if !self.dither {
+            return 0.0;
+        }
+        let steps = match self.color_mode {
+            ColorMode::NoColor => self.theme.glyphs.len(),
+            _ => self.theme.fire.len() * self.theme.glyphs.len(),
+        };
+        (bayer(x, y) - 0.5) / steps as f32
     }
 
-    fn fill_color(&self, color: f32) {
-        let ((fg, bg), chr) = self.fire_palette((color.powf(0.9) * 100.0) as u16);
+    fn fill_color(&self, buffer: &mut Vec<u8>, color: f32, dither: f32) -> io::Result<()> {
+        let luma = (color.powf(0.9) + dither).clamp(0.0, 0.99);
+        let ((bg, fg), chr) = self.fire_palette((luma * 100.0) as u16);
 
-        print!("{}{}{}", bg, fg, chr);
+        write_bg(buffer, TermColor::Indexed(bg), self.color_mode)?;
+        write_fg(buffer, TermColor::Indexed(fg), self.color_mode)?;
+        write!(buffer, "{}", chr)
     }
 
-    fn fire_palette(&self, luma: u16) -> ((&str, &str), char) {
-        let (v_col, v_char) = self.get_indexes(luma, FIRE_PALETTE.len());
-        (FIRE_PALETTE[v_col], CHARS_GRADIENT[v_char])
+    fn fire_palette(&self, luma: u16) -> ((u8, u8), char) {
+        //without colors the whole glyph ramp goes from dark to bright
+        if self.color_mode == ColorMode::NoColor {
+            let (_, v_char) = self.get_indexes(luma, 1);
+            return (self.theme.default_color, self.theme.glyphs[v_char]);
+        }
+        let (v_col, v_char) = self.get_indexes(luma, self.theme.fire.len());
+        (self.theme.fire[v_col], self.theme.glyphs[v_char])
     }
 
     fn get_indexes(&self, luma: u16, color_len: usize) -> (usize, usize) {
         let palette_range = 100;
-        let char_len: u16 = CHARS_GRADIENT.len() as u16;
+        let char_len: u16 = self.theme.glyphs.len() as u16;
         let color_len: u16 = color_len as u16;
         let v_col = luma * color_len / palette_range;
         let v_char = ((luma * color_len) % palette_range) * char_len / palette_range;
@@ -86,3 +144,7 @@ impl FireDrawer {
         (v_col as usize, v_char as usize)
     }
 }
+
+fn fire_height(size: (u16, u16), top: u16) -> usize {
+    (size.1.saturating_sub(top) as usize).min(FIRE_HEIGHT)
+}
@@ -1,4 +1,3 @@
-use crate::asciicontext::AsciiContext;
 use crate::drawables::*;
 use crate::drawingcontext::DrawingContext;
 use crate::enemy::*;
@@ -24,7 +23,7 @@ pub struct Powerup {
 }
 
 impl TerminalDrawble for Powerup {
-    fn draw(&self, ctx: &mut AsciiContext) {
+    fn draw(&self, ctx: &mut dyn DrawingContext) {
         let sides = 6;
         let radius = self.get_description() * (0.8 + self.life.cos() * 0.3);
         let mut triangles = vec![EMPTY_TRIANGLE; sides];
@@ -51,15 +50,21 @@ impl TerminalDrawble for Powerup {
             triangles[i] = Triangle {
                 points: [self.position, point_left, point_right],
                 colors: [color1, color0, color0],
-                color_palette: match self.size {
-                    PowerupSize::Shield => ColorPalette::Blue,
-                    PowerupSize::SplitFire => ColorPalette::Green,
-                    PowerupSize::PiercingBullets => ColorPalette::Red,
-                    PowerupSize::RapidFire => ColorPalette::Gray,
-                },
+                color_palettes: [self.get_palette(); 3],
             };
         }
-        ctx.add_triangles(&triangles);
+        ctx.add_shape(&triangles);
+    }
+}
+
+impl Powerup {
+    pub fn get_palette(&self) -> ColorPalette {
+        match self.size {
+            PowerupSize::Shield => ColorPalette::Blue,
+            PowerupSize::SplitFire => ColorPalette::Green,
+            PowerupSize::PiercingBullets => ColorPalette::Red,
+            PowerupSize::RapidFire => ColorPalette::Gray,
+        }
     }
 }
 
@@ -86,6 +91,10 @@ impl Sprite for Powerup {
         }
     }
 
+    fn clamp(&mut self, bounds: Vec2) {
+        self.position = clamp_position(self.position, bounds);
+    }
+
     fn is_alive(&self) -> bool {
         return true;
     }
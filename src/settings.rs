@@ -0,0 +1,148 @@
+use crate::drawables::*;
+use crate::theme::Theme;
+
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub render_mode: RenderMode,
+    pub color_mode: ColorMode,
+    pub cell_aspect: f32,
+    pub theme: Theme,
+    pub vector: bool,
+    pub dither: bool,
+    //half-life in seconds of the phosphor trails, none clears every frame
+    pub trails: Option<f32>,
+    //no bloom, flashes or screen shake
+    pub reduced_motion: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            render_mode: RenderMode::Cell,
+            color_mode: ColorMode::detect(),
+            cell_aspect: detect_cell_aspect(),
+            theme: Theme::default(),
+            vector: false,
+            dither: false,
+            trails: None,
+            reduced_motion: false,
+        }
+    }
+}
+
+//printed when the arguments can't be used, same list as the readme
+pub const USAGE: &str = "\
+usage: terminoids [options]
+
+--render cell         : one pixel per character (default)
+--render half-block   : two pixels per character, doubles the vertical resolution
+--render braille      : 2x4 dots per character using braille patterns
+--cell-aspect <ratio> : height/width of a character cell, detected from the terminal when it reports
+                        its pixel size, otherwise 17/8
+--color <mode>        : truecolor, 256, 16 or none, overrides the detection
+--theme <name>        : classic (default), ascii, high-contrast or monochrome
+--theme <file.json>   : a custom theme, see asset/themes for the format
+--vector              : draw ships and asteroids as outlines, like a vector display
+--dither              : ordered dithering between shading steps, smooths gradients with few colors
+--trails <half-life>  : phosphor persistence, moving objects leave trails fading over the half-life in seconds
+--reduced-motion      : no bloom, screen flashes or screen shake";
+
+impl Settings {
+    //the first bad argument stops the parsing, nothing runs with half the options applied
+    pub fn from_args(args: &[String]) -> Result<Settings, String> {
+        let mut settings = Settings::default();
+
+        let mut args = args.iter().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--render" => match args.next().and_then(|name| RenderMode::from_name(name)) {
+                    Some(render_mode) => settings.render_mode = render_mode,
+                    None => {
+                        return Err(String::from(
+                            "--render expects one of: cell, half-block, braille",
+                        ))
+                    }
+                },
+                "--cell-aspect" => match args.next().and_then(|v| v.parse::<f32>().ok()) {
+                    Some(aspect) if aspect.is_finite() && aspect > 0.0 => {
+                        settings.cell_aspect = aspect
+                    }
+                    _ => {
+                        return Err(String::from(
+                            "--cell-aspect expects a finite positive number, e.g. 2.0",
+                        ))
+                    }
+                },
+                "--color" => match args.next().and_then(|name| ColorMode::from_name(name)) {
+                    Some(color_mode) => settings.color_mode = color_mode,
+                    None => {
+                        return Err(String::from(
+                            "--color expects one of: truecolor, 256, 16, none",
+                        ))
+                    }
+                },
+                "--theme" => match args.next() {
+                    Some(name) => settings.theme = Theme::load(name)?,
+                    None => {
+                        return Err(format!(
+                            "--theme expects one of: {}, or a path to a json theme",
+                            Theme::preset_names().join(", ")
+                        ))
+                    }
+                },
+                "--vector" => settings.vector = true,
+                "--dither" => settings.dither = true,
+                "--reduced-motion" => settings.reduced_motion = true,
+                "--trails" => match args.next().and_then(|v| v.parse::<f32>().ok()) {
+                    Some(half_life) if half_life > 0.0 => settings.trails = Some(half_life),
+                    _ => {
+                        return Err(String::from(
+                            "--trails expects a half-life in seconds, e.g. 0.1",
+                        ))
+                    }
+                },
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+
+        Ok(settings)
+    }
+}
+
+//cell height over width from the pixel size in TIOCGWINSZ, many terminals leave it at 0
+fn detect_cell_aspect() -> f32 {
+    let cells = termion::terminal_size();
+    let pixels = termion::terminal_size_pixels();
+    match (cells, pixels) {
+        (Ok((cols, rows)), Ok((width, height)))
+            if cols > 0 && rows > 0 && width > 0 && height > 0 =>
+        {
+            (height as f32 / rows as f32) / (width as f32 / cols as f32)
+        }
+        _ => DEFAULT_CELL_ASPECT,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Settings, String> {
+        let args: Vec<String> = ["terminoids"]
+            .iter()
+            .chain(args)
+            .map(|arg| arg.to_string())
+            .collect();
+        Settings::from_args(&args)
+    }
+
+    #[test]
+    fn bad_arguments_are_errors() {
+        assert!(parse(&["--render", "braille", "--vector"]).is_ok());
+        assert!(parse(&["--render", "sixel"]).is_err());
+        assert!(parse(&["--cell-aspect", "inf"]).is_err());
+        assert!(parse(&["--trails"]).is_err());
+        assert!(parse(&["--theme", "no-such-theme"]).is_err());
+        assert!(parse(&["--fullscreen"]).is_err());
+    }
+}
@@ -1,4 +1,3 @@
-use crate::asciicontext::AsciiContext;
 use crate::bullet::*;
 use crate::drawables::*;
 use crate::drawingcontext::DrawingContext;
@@ -25,7 +24,7 @@ pub struct Ship {
 }
 
 impl TerminalDrawble for Ship {
-    fn draw(&self, ctx: &mut AsciiContext) {
+    fn draw(&self, ctx: &mut dyn DrawingContext) {
         if self.spawning > 0.0 {
             let r = self.spawning * 13.0;
             let n = 64;
@@ -75,10 +74,10 @@ impl TerminalDrawble for Ship {
                 triangles[i] = Triangle {
                     points: [self.position, point_left, point_right],
                     colors: [color1, color0, color0],
-                    color_palette: ColorPalette::Blue,
+                    color_palettes: [ColorPalette::Blue, ColorPalette::Cyan, ColorPalette::Cyan],
                 };
             }
-            ctx.add_triangles(&triangles);
+            ctx.add_shape(&triangles);
         }
 
         let front = (
@@ -105,16 +104,16 @@ impl TerminalDrawble for Ship {
             Triangle {
                 points: [front, back, left],
                 colors: [0.8, 1.0, 0.2],
-                color_palette: ColorPalette::Gray,
+                color_palettes: [ColorPalette::Gray; 3],
             },
             Triangle {
                 points: [right, back, front],
                 colors: [0.7, 1.0, 0.7],
-                color_palette: ColorPalette::Gray,
+                color_palettes: [ColorPalette::Gray; 3],
             },
         ]
         .to_vec();
-        ctx.add_triangles(&triangles);
+        ctx.add_shape(&triangles);
     }
 }
 
@@ -161,6 +160,10 @@ impl Sprite for Ship {
         self.piercing -= delta;
     }
 
+    fn clamp(&mut self, bounds: Vec2) {
+        self.position = clamp_position(self.position, bounds);
+    }
+
     fn is_alive(&self) -> bool {
         self.life >= 0
     }
@@ -6,6 +6,9 @@ use crate::ship::Ship;
 pub trait Sprite {
     fn update(&mut self, camera: &Camera, delta: f32);
     fn is_alive(&self) -> bool;
+
+    //pulls the sprite back inside the bounds when the screen shrinks
+    fn clamp(&mut self, _bounds: Vec2) {}
 }
 
 pub trait Collidable {
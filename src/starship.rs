@@ -1,4 +1,3 @@
-use crate::asciicontext::AsciiContext;
 use crate::drawables::*;
 use crate::drawingcontext::DrawingContext;
 use crate::enemy::*;
@@ -27,7 +26,7 @@ pub struct StarShip {
 }
 
 impl TerminalDrawble for StarShip {
-    fn draw(&self, ctx: &mut AsciiContext) {
+    fn draw(&self, ctx: &mut dyn DrawingContext) {
         match self.size {
             StarShipSize::Flying => {
                 let (sides, radius) = self.get_description();
@@ -59,15 +58,15 @@ impl TerminalDrawble for StarShip {
                 triangles[0] = Triangle {
                     points: [self.position, point_left, point_head],
                     colors: [color_a, color_a, color_a],
-                    color_palette: ColorPalette::Yellow,
+                    color_palettes: [ColorPalette::Yellow; 3],
                 };
                 triangles[1] = Triangle {
                     points: [self.position, point_right, point_head],
                     colors: [color_b, color_b, color_b],
-                    color_palette: ColorPalette::Yellow,
+                    color_palettes: [ColorPalette::Yellow; 3],
                 };
 
-                ctx.add_triangles(&triangles);
+                ctx.add_shape(&triangles);
             }
             StarShipSize::SmallCluster => {
                 let (sides, radius) = self.get_description();
@@ -103,15 +102,15 @@ impl TerminalDrawble for StarShip {
                     triangles[i * 2] = Triangle {
                         points: [self.position, point_left, point_head],
                         colors: [color_a, color_a, color_a],
-                        color_palette: ColorPalette::Magenta,
+                        color_palettes: [ColorPalette::Magenta; 3],
                     };
                     triangles[i * 2 + 1] = Triangle {
                         points: [self.position, point_right, point_head],
                         colors: [color_b, color_b, color_b],
-                        color_palette: ColorPalette::Magenta,
+                        color_palettes: [ColorPalette::Magenta; 3],
                     };
                 }
-                ctx.add_triangles(&triangles);
+                ctx.add_shape(&triangles);
             }
             StarShipSize::MediumCluster => {
                 let (sides, radius) = self.get_description();
@@ -147,12 +146,12 @@ impl TerminalDrawble for StarShip {
                     triangles[i * 2] = Triangle {
                         points: [self.position, point_left, point_head],
                         colors: [color_a, color_a, color_a],
-                        color_palette: ColorPalette::Magenta,
+                        color_palettes: [ColorPalette::Magenta; 3],
                     };
                     triangles[i * 2 + 1] = Triangle {
                         points: [self.position, point_right, point_head],
                         colors: [color_b, color_b, color_b],
-                        color_palette: ColorPalette::Magenta,
+                        color_palettes: [ColorPalette::Magenta; 3],
                     };
                 }
 
@@ -185,15 +184,15 @@ impl TerminalDrawble for StarShip {
                     triangles[i * 2 + 8] = Triangle {
                         points: [self.position, point_left, point_head],
                         colors: [color_a, color_a, color_a],
-                        color_palette: ColorPalette::Blue,
+                        color_palettes: [ColorPalette::Blue; 3],
                     };
                     triangles[i * 2 + 9] = Triangle {
                         points: [self.position, point_right, point_head],
                         colors: [color_b, color_b, color_b],
-                        color_palette: ColorPalette::Blue,
+                        color_palettes: [ColorPalette::Blue; 3],
                     };
                 }
-                ctx.add_triangles(&triangles);
+                ctx.add_shape(&triangles);
             }
             StarShipSize::BigCluster => {
                 let (sides, radius) = self.get_description();
@@ -210,7 +209,7 @@ impl TerminalDrawble for StarShip {
                 triangles[0] = Triangle {
                     points: [point_a, point_b, point_c],
                     colors: [0.3, 0.3, 0.3],
-                    color_palette: ColorPalette::Red,
+                    color_palettes: [ColorPalette::Red; 3],
                 };
 
                 let a = self.angle + std::f32::consts::FRAC_PI_3;
@@ -223,7 +222,7 @@ impl TerminalDrawble for StarShip {
                 triangles[1] = Triangle {
                     points: [point_a, point_b, point_c],
                     colors: [0.5, 0.5, 0.5],
-                    color_palette: ColorPalette::Red,
+                    color_palettes: [ColorPalette::Red; 3],
                 };
 
                 let n = 4.0;
@@ -256,12 +255,12 @@ impl TerminalDrawble for StarShip {
                     triangles[i * 2 + 2] = Triangle {
                         points: [self.position, point_left, point_head],
                         colors: [color_a, color_a, color_a],
-                        color_palette: ColorPalette::Magenta,
+                        color_palettes: [ColorPalette::Magenta; 3],
                     };
                     triangles[i * 2 + 3] = Triangle {
                         points: [self.position, point_right, point_head],
                         colors: [color_b, color_b, color_b],
-                        color_palette: ColorPalette::Magenta,
+                        color_palettes: [ColorPalette::Magenta; 3],
                     };
                 }
 
@@ -294,15 +293,15 @@ impl TerminalDrawble for StarShip {
                     triangles[i * 2 + 10] = Triangle {
                         points: [self.position, point_left, point_head],
                         colors: [color_a, color_a, color_a],
-                        color_palette: ColorPalette::Blue,
+                        color_palettes: [ColorPalette::Blue; 3],
                     };
                     triangles[i * 2 + 11] = Triangle {
                         points: [self.position, point_right, point_head],
                         colors: [color_b, color_b, color_b],
-                        color_palette: ColorPalette::Blue,
+                        color_palettes: [ColorPalette::Blue; 3],
                     };
                 }
-                ctx.add_triangles(&triangles);
+                ctx.add_shape(&triangles);
             }
         };
     }
@@ -350,6 +349,10 @@ impl Sprite for StarShip {
         }
     }
 
+    fn clamp(&mut self, bounds: Vec2) {
+        self.position = clamp_position(self.position, bounds);
+    }
+
     fn is_alive(&self) -> bool {
         return true;
     }
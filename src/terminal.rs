@@ -0,0 +1,121 @@
+use std::io::{stdout, Stdout, Write};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use termion::raw::RawTerminal;
+use termion::{color, cursor, screen, style};
+
+//message of a panic, printed once the terminal is usable again
+static PANIC_MESSAGE: Mutex<Option<String>> = Mutex::new(None);
+
+//set from the signal handlers, picked up by the screens once per frame
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+static CONTINUED: AtomicBool = AtomicBool::new(false);
+
+//the terminal reports focus changes as CSI I and CSI O
+const FOCUS_REPORTING_ON: &str = "\x1b[?1004h";
+const FOCUS_REPORTING_OFF: &str = "\x1b[?1004l";
+pub const FOCUS_OUT: &[u8] = b"\x1b[O";
+
+fn enter(out: &mut dyn Write) {
+    write!(
+        out,
+        "{}{}{}",
+        screen::ToAlternateScreen,
+        cursor::Hide,
+        FOCUS_REPORTING_ON
+    )
+    .unwrap();
+    out.flush().unwrap();
+}
+
+fn leave(out: &mut dyn Write) {
+    //nothing left to do if the terminal is gone
+    let _ = write!(
+        out,
+        "{}{}{}{}{}{}",
+        FOCUS_REPORTING_OFF,
+        style::Reset,
+        color::Bg(color::Reset),
+        color::Fg(color::Reset),
+        cursor::Show,
+        screen::ToMainScreen
+    );
+    let _ = out.flush();
+}
+
+//owns the look of the terminal while the game runs, dropping it puts everything back
+//it has to be dropped before the raw terminal so cooked mode comes back last
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn enter() -> TerminalGuard {
+        enter(&mut stdout());
+        TerminalGuard
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        leave(&mut stdout());
+    }
+}
+
+//the default hook would print on the alternate screen while still in raw mode
+//so the message is kept until the guards are dropped
+pub fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let message = format!("{}", info);
+        if let Ok(mut slot) = PANIC_MESSAGE.lock() {
+            *slot = Some(message);
+        }
+    }));
+}
+
+pub fn print_panic_message() {
+    if let Some(message) = PANIC_MESSAGE.lock().ok().and_then(|mut slot| slot.take()) {
+        eprintln!("terminoids {}", message);
+    }
+}
+
+extern "C" fn on_stop(_: libc::c_int) {
+    STOP_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+extern "C" fn on_continue(_: libc::c_int) {
+    CONTINUED.store(true, Ordering::SeqCst);
+}
+
+fn handler(f: extern "C" fn(libc::c_int)) -> libc::sighandler_t {
+    f as libc::sighandler_t
+}
+
+//a SIGTSTP from outside is only noted, the terminal is put back before actually stopping
+pub fn install_signal_handlers() {
+    unsafe {
+        libc::signal(libc::SIGTSTP, handler(on_stop));
+        libc::signal(libc::SIGCONT, handler(on_continue));
+    }
+}
+
+//hands the terminal back to the shell and stops the process like ctrl-z normally would
+//returns once the job is continued, with raw mode and the alternate screen back on
+pub fn suspend(stdout: &mut RawTerminal<Stdout>) {
+    leave(stdout);
+    stdout.suspend_raw_mode().unwrap();
+    unsafe {
+        libc::signal(libc::SIGTSTP, libc::SIG_DFL);
+        libc::raise(libc::SIGTSTP);
+        libc::signal(libc::SIGTSTP, handler(on_stop));
+    }
+    stdout.activate_raw_mode().unwrap();
+    enter(stdout);
+}
+
+//suspends if a stop was asked for from outside, true when the screen has to be drawn again
+pub fn job_control(stdout: &mut RawTerminal<Stdout>) -> bool {
+    if STOP_REQUESTED.swap(false, Ordering::SeqCst) {
+        suspend(stdout);
+    }
+    CONTINUED.swap(false, Ordering::SeqCst)
+}
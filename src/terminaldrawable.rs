@@ -1,5 +1,5 @@
-use crate::asciicontext::AsciiContext;
+use crate::drawingcontext::DrawingContext;
 
 pub trait TerminalDrawble {
-    fn draw(&self, ctx: &mut AsciiContext);
+    fn draw(&self, ctx: &mut dyn DrawingContext);
 }
@@ -0,0 +1,205 @@
+use crate::drawables::*;
+use serde::Deserialize;
+use std::fs;
+use unicode_width::UnicodeWidthChar;
+
+//themes built into the binary, any other name is read as a path to a json file
+const PRESETS: [(&str, &str); 4] = [
+    ("classic", include_str!("../asset/themes/classic.json")),
+    ("ascii", include_str!("../asset/themes/ascii.json")),
+    (
+        "high-contrast",
+        include_str!("../asset/themes/high-contrast.json"),
+    ),
+    (
+        "monochrome",
+        include_str!("../asset/themes/monochrome.json"),
+    ),
+];
+
+//pairs are (background, foreground) 256-color indexes, from dark to bright
+//gradient holds the stops used by the truecolor renderer, indexes into ANSI_RGB
+#[derive(Clone, Debug, Deserialize)]
+pub struct Palette {
+    pub pairs: Vec<(u8, u8)>,
+    pub gradient: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Palettes {
+    pub red: Palette,
+    pub green: Palette,
+    pub blue: Palette,
+    pub yellow: Palette,
+    pub magenta: Palette,
+    pub cyan: Palette,
+    pub gray: Palette,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SymbolStyle {
+    pub glyph: char,
+    pub colors: (u8, u8),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Symbols {
+    pub ball: SymbolStyle,
+    pub piercing: [SymbolStyle; 2],
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TextColors {
+    pub text: (u8, u8),
+    pub menu: (u8, u8),
+    pub warning: (u8, u8),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Theme {
+    pub default_color: (u8, u8),
+    //shading characters, from dark to bright
+    pub glyphs: Vec<char>,
+    pub symbols: Symbols,
+    pub text: TextColors,
+    pub fire: Vec<(u8, u8)>,
+    pub palettes: Palettes,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::load("classic").unwrap()
+    }
+}
+
+impl Theme {
+    pub fn load(name: &str) -> Result<Theme, String> {
+        let json = match PRESETS.iter().find(|(preset, _)| *preset == name) {
+            Some((_, json)) => json.to_string(),
+            None => fs::read_to_string(name).map_err(|e| format!("Theme {} : {}", name, e))?,
+        };
+
+        let theme: Theme =
+            serde_json::from_str(&json).map_err(|e| format!("Theme {} : {}", name, e))?;
+        theme
+            .validate()
+            .map_err(|e| format!("Theme {} : {}", name, e))?;
+
+        Ok(theme)
+    }
+
+    pub fn preset_names() -> Vec<&'static str> {
+        PRESETS.iter().map(|(name, _)| *name).collect()
+    }
+
+    pub fn palette(&self, palette: ColorPalette) -> Option<&Palette> {
+        match palette {
+            ColorPalette::Red => Some(&self.palettes.red),
+            ColorPalette::Green => Some(&self.palettes.green),
+            ColorPalette::Blue => Some(&self.palettes.blue),
+            ColorPalette::Yellow => Some(&self.palettes.yellow),
+            ColorPalette::Magenta => Some(&self.palettes.magenta),
+            ColorPalette::Cyan => Some(&self.palettes.cyan),
+            ColorPalette::Gray => Some(&self.palettes.gray),
+            ColorPalette::Symbol(_) => None,
+        }
+    }
+
+    pub fn symbol(&self, symbol: Symbol) -> &SymbolStyle {
+        match symbol {
+            Symbol::Ball => &self.symbols.ball,
+            Symbol::Piercing0 => &self.symbols.piercing[0],
+            Symbol::Piercing1 => &self.symbols.piercing[1],
+        }
+    }
+
+    pub fn text_colors(&self, palette: TextColorPalette) -> (TermColor, TermColor) {
+        let (bg, fg) = match palette {
+            TextColorPalette::Text => self.text.text,
+            TextColorPalette::Menu => self.text.menu,
+            TextColorPalette::Warning => self.text.warning,
+            TextColorPalette::Custom(bg, fg) => return (bg, fg),
+        };
+        (TermColor::Indexed(bg), TermColor::Indexed(fg))
+    }
+
+    //the renderers index into these without checking
+    fn validate(&self) -> Result<(), String> {
+        if self.glyphs.is_empty() {
+            return Err(String::from("glyphs can't be empty"));
+        }
+        if self.fire.is_empty() {
+            return Err(String::from("fire can't be empty"));
+        }
+        //every glyph fills exactly one cell of the grid
+        let symbols = [
+            &self.symbols.ball,
+            &self.symbols.piercing[0],
+            &self.symbols.piercing[1],
+        ];
+        let mut glyphs = self.glyphs.iter().chain(symbols.iter().map(|s| &s.glyph));
+        if let Some(glyph) = glyphs.find(|glyph| glyph.width() != Some(1)) {
+            return Err(format!(
+                "glyph {:?} has to be one printable single width character",
+                glyph
+            ));
+        }
+        let palettes = &self.palettes;
+        for palette in [
+            &palettes.red,
+            &palettes.green,
+            &palettes.blue,
+            &palettes.yellow,
+            &palettes.magenta,
+            &palettes.cyan,
+            &palettes.gray,
+        ] {
+            if palette.pairs.is_empty() {
+                return Err(String::from("palette pairs can't be empty"));
+            }
+            if palette.gradient.len() < 2 {
+                return Err(String::from("palette gradients need at least 2 stops"));
+            }
+            if palette
+                .gradient
+                .iter()
+                .any(|&stop| stop as usize >= ANSI_RGB.len())
+            {
+                return Err(String::from("gradient stops go from 0 to 15"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classic_with_glyph(glyph: char) -> Theme {
+        let mut theme: Theme = serde_json::from_str(PRESETS[0].1).unwrap();
+        theme.glyphs[1] = glyph;
+        theme
+    }
+
+    #[test]
+    fn presets_load() {
+        for name in Theme::preset_names() {
+            if let Err(e) = Theme::load(name) {
+                panic!("{}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn glyphs_fill_one_cell() {
+        assert!(classic_with_glyph('#').validate().is_ok());
+        assert!(classic_with_glyph('\u{65e5}').validate().is_err());
+        assert!(classic_with_glyph('\u{7}').validate().is_err());
+        assert!(classic_with_glyph('\u{301}').validate().is_err());
+
+        let mut theme = classic_with_glyph('#');
+        theme.symbols.ball.glyph = '\u{1f600}';
+        assert!(theme.validate().is_err());
+    }
+}